@@ -15,7 +15,17 @@ async fn main(_spawner: Spawner) {
     let mut btn_b = board.btn_b;
 
     display.set_brightness(display::Brightness::MAX);
-    display.scroll("Hello, World!").await;
+
+    // Scroll the greeting until a button is pressed
+    let mut greeting = display::Animation::new(
+        display::AnimationData::Bytes(b"Hello, World!"),
+        display::AnimationEffect::Slide,
+        Duration::from_secs(6),
+    )
+    .unwrap();
+    greeting.set_repeat(display::Repeat::Forever);
+    select(display.play(&mut greeting), btn_a.wait_for_low()).await;
+    display.clear();
     defmt::info!("Application started, press buttons!");
     loop {
         match select(btn_a.wait_for_low(), btn_b.wait_for_low()).await {
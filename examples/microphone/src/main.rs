@@ -52,43 +52,6 @@ async fn main(_s: Spawner) {
 /// Display the sound level as an expanding indicator on the LED matrix
 /// Starts with the center column and expands outward as volume increases
 async fn display_sound_indicator(display: &mut LedMatrix, length: Duration, sound_level: u8) {
-    let mut frame = Frame::<5, 5>::empty();
-
-    const CENTER_COLUMN: usize = 2;
-    const MAX_ROWS: usize = 5;
-
-    // Convert sound level (0-255) to number of rows to light up (0-5)
-    let num_rows = match sound_level {
-        0 => 0,
-        1..=51 => 1,
-        52..=102 => 2,
-        103..=153 => 3,
-        154..=204 => 4,
-        205..=255 => MAX_ROWS,
-    };
-
-    if num_rows > 0 {
-        // Light up rows from bottom to top, starting with center column
-        for row in (MAX_ROWS - num_rows)..MAX_ROWS {
-            frame.set(CENTER_COLUMN, row);
-        }
-
-        // For medium levels, expand to adjacent columns
-        if num_rows >= 3 {
-            for row in (MAX_ROWS - num_rows)..MAX_ROWS {
-                frame.set(1, row);
-                frame.set(3, row);
-            }
-        }
-
-        // For high levels, expand to all columns
-        if num_rows >= 4 {
-            for row in (MAX_ROWS - num_rows)..MAX_ROWS {
-                frame.set(0, row);
-                frame.set(4, row);
-            }
-        }
-    }
-
+    let frame = Frame::<5, 5>::level_meter(sound_level as u32, u8::MAX as u32);
     display.display(frame, length).await;
 }
@@ -1,5 +1,5 @@
 pub use embassy_nrf::config::Config;
-use embassy_nrf::gpio::{Input, Level, Output, OutputDrive, Pin, Pull};
+use embassy_nrf::gpio::{Flex, Input, OutputDrive, Pin, Pull};
 pub use embassy_nrf::interrupt::Priority;
 use embassy_nrf::peripherals::{
     P0_00, P0_01, P0_02, P0_03, P0_04, P0_05, P0_06, P0_08, P0_09, P0_10, P0_12, P0_13, P0_16, P0_17, P0_20, P0_26,
@@ -11,10 +11,54 @@ use embassy_nrf::Peri;
 
 #[cfg(feature = "trouble")]
 use crate::ble;
-use crate::display::LedMatrix as LedMatrixDriver;
+use crate::display::{LedMatrix as LedMatrixDriver, Refresher, SensePin, SharedFrame};
 
-/// LED matrix peripheral for the micro:bit
-pub type LedMatrix = LedMatrixDriver<Output<'static>, 5, 5>;
+/// LED matrix peripheral for the micro:bit.
+///
+/// The display is the main consumer of power on an idle board. Use
+/// [`set_power_mode`](LedMatrix::set_power_mode) when it is not needed, or
+/// [`SharedDisplay::set_power_mode`] when it is refreshed by a [`DisplayRefresher`], which lets
+/// the executor sleep until the display is enabled again.
+pub type LedMatrix = LedMatrixDriver<Flex<'static>, 5, 5>;
+
+/// Shared frame buffer for the micro:bit LED matrix
+pub type SharedDisplay = SharedFrame<5, 5>;
+
+/// Power modes of the board
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerMode {
+    /// Everything is running
+    Active,
+    /// The display is turned off with all its pins driven low, keeping its frame for when the
+    /// board is active again. With nothing left to refresh, the executor sleeps until the next
+    /// event, such as a button press or a timer.
+    Idle,
+}
+
+impl LedMatrix {
+    /// Switch the display to the power mode, see [`LedMatrixDriver::disable`]
+    pub fn set_power_mode(&mut self, mode: PowerMode) {
+        match mode {
+            PowerMode::Active => self.enable(),
+            PowerMode::Idle => self.disable(),
+        }
+    }
+}
+
+impl SharedDisplay {
+    /// Switch the display refreshed by a [`DisplayRefresher`] to the power mode, see
+    /// [`SharedFrame::disable`]
+    pub fn set_power_mode(&self, mode: PowerMode) {
+        match mode {
+            PowerMode::Active => self.enable(),
+            PowerMode::Idle => self.disable(),
+        }
+    }
+}
+
+/// Background refresh engine for the micro:bit LED matrix
+pub type DisplayRefresher = Refresher<'static, Flex<'static>, 5, 5>;
 
 /// Button 'A'
 pub type Button = Input<'static>;
@@ -116,19 +160,19 @@ impl Microbit {
         let p = embassy_nrf::init(config);
         // LED Matrix
         let rows = [
-            output_pin(p.P0_21),
-            output_pin(p.P0_22),
-            output_pin(p.P0_15),
-            output_pin(p.P0_24),
-            output_pin(p.P0_19),
+            matrix_pin(p.P0_21),
+            matrix_pin(p.P0_22),
+            matrix_pin(p.P0_15),
+            matrix_pin(p.P0_24),
+            matrix_pin(p.P0_19),
         ];
 
         let cols = [
-            output_pin(p.P0_28),
-            output_pin(p.P0_11),
-            output_pin(p.P0_31),
-            output_pin(p.P1_05),
-            output_pin(p.P0_30),
+            matrix_pin(p.P0_28),
+            matrix_pin(p.P0_11),
+            matrix_pin(p.P0_31),
+            matrix_pin(p.P1_05),
+            matrix_pin(p.P0_30),
         ];
 
         Self {
@@ -176,8 +220,29 @@ impl Microbit {
             ),
         }
     }
+
+    /// Switch the peripherals driven by the board to the power mode. Once the display has been
+    /// moved out of the board, use [`LedMatrix::set_power_mode`] or
+    /// [`SharedDisplay::set_power_mode`] instead.
+    pub fn set_power_mode(&mut self, mode: PowerMode) {
+        self.display.set_power_mode(mode);
+    }
 }
 
-fn output_pin(pin: Peri<'static, impl Pin>) -> Output<'static> {
-    Output::new(pin, Level::Low, OutputDrive::Standard)
+// Matrix pins are outputs, but can be read as inputs to sense the ambient light
+fn matrix_pin(pin: Peri<'static, impl Pin>) -> Flex<'static> {
+    let mut pin = Flex::new(pin);
+    pin.set_low();
+    pin.set_as_output(OutputDrive::Standard);
+    pin
+}
+
+impl SensePin for Flex<'_> {
+    fn set_as_input(&mut self) {
+        Flex::set_as_input(self, Pull::None);
+    }
+
+    fn set_as_output(&mut self) {
+        Flex::set_as_output(self, OutputDrive::Standard);
+    }
 }
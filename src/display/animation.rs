@@ -0,0 +1,946 @@
+//! Animations of frame sequences with transition effects
+//!
+//! An [`Animation`] does not own the display. It is a state machine producing the frame to show
+//! at a given point in time, which can be played on a [`LedMatrix`](super::LedMatrix) using
+//! [`play`](super::LedMatrix::play) or applied to a [`SharedFrame`](super::SharedFrame) by any task.
+//! Since the animation is only borrowed while playing, it can be paused, resumed or stopped
+//! between steps, e.g. when an event cancels the playing future.
+use embassy_time::{Duration, Instant};
+
+use super::fonts::{self, Font};
+use super::{Frame, FrameSequence, GreyscaleFrame};
+
+/// An effect filter to apply for an animation
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AnimationEffect {
+    /// No effect
+    None,
+    /// Sliding effect, moving frames to the left
+    Slide,
+    /// Sliding effect, moving frames to the right
+    SlideRight,
+    /// Sliding effect, moving frames up
+    SlideUp,
+    /// Sliding effect, moving frames down
+    SlideDown,
+    /// Sliding effect, moving frames diagonally up and to the left
+    SlideUpLeft,
+    /// Sliding effect, moving frames diagonally up and to the right
+    SlideUpRight,
+    /// Sliding effect, moving frames diagonally down and to the left
+    SlideDownLeft,
+    /// Sliding effect, moving frames diagonally down and to the right
+    SlideDownRight,
+    /// Wipe effect, revealing the next frame column by column from the left
+    Wipe,
+    /// Dissolve effect, revealing the next frame pixel by pixel in a random order given by the seed
+    Dissolve(u32),
+    /// Fade effect, fading out each frame before fading in the next
+    Fade,
+}
+
+impl AnimationEffect {
+    // Number of animation steps to transition from one frame to the next
+    fn steps<const XSIZE: usize, const YSIZE: usize>(&self) -> usize {
+        match self {
+            AnimationEffect::None => 1,
+            AnimationEffect::Slide | AnimationEffect::SlideRight | AnimationEffect::Wipe => XSIZE,
+            AnimationEffect::SlideUp | AnimationEffect::SlideDown => YSIZE,
+            AnimationEffect::SlideUpLeft
+            | AnimationEffect::SlideUpRight
+            | AnimationEffect::SlideDownLeft
+            | AnimationEffect::SlideDownRight => XSIZE.max(YSIZE),
+            AnimationEffect::Dissolve(_) => XSIZE * YSIZE,
+            AnimationEffect::Fade => 2 * GreyscaleFrame::<XSIZE, YSIZE>::MAX_LEVEL as usize,
+        }
+    }
+}
+
+// Step at which the pixel is revealed by the dissolve effect
+fn dissolve_step(seed: u32, pixel: usize, steps: usize) -> usize {
+    // Integer hash mixing the seed and pixel index
+    let mut h = seed ^ (pixel as u32).wrapping_mul(0x9e37_79b9);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^= h >> 16;
+    h as usize % steps
+}
+
+/// Source of the frames of an animation
+pub enum AnimationData<'a, const XSIZE: usize, const YSIZE: usize, const WORDS: usize> {
+    /// A slice of frames
+    Frames(&'a [Frame<XSIZE, YSIZE, WORDS>]),
+    /// A slice of Latin-1 characters rendered with the default font
+    Bytes(&'a [u8]),
+    /// Text rendered with the real width of each glyph, separated by `gap` empty columns.
+    ///
+    /// Every step scrolls the text one column to the left, the animation effect is ignored.
+    Text {
+        /// Text to scroll
+        text: &'a str,
+        /// Font used to render the text
+        font: &'a dyn Font,
+        /// Empty columns between characters
+        gap: usize,
+    },
+    /// A frame sequence, played with the greyscale levels and durations of its frames.
+    ///
+    /// Every step shows the next frame, the animation effect is ignored.
+    Sequence(FrameSequence<'a>),
+}
+
+impl<'a, const XSIZE: usize, const YSIZE: usize, const WORDS: usize> AnimationData<'a, XSIZE, YSIZE, WORDS> {
+    fn len(&self) -> usize {
+        match self {
+            AnimationData::Frames(f) => f.len(),
+            AnimationData::Bytes(f) => f.len(),
+            AnimationData::Text { text, font, gap } => font.text_width(text, *gap),
+            AnimationData::Sequence(s) => s.len(),
+        }
+    }
+
+    fn frame(&self, idx: usize) -> Frame<XSIZE, YSIZE, WORDS> {
+        match self {
+            AnimationData::Frames(f) => f[idx],
+            AnimationData::Bytes(f) => f[idx].into(),
+            AnimationData::Text { text, font, gap } => fonts::render_text(*font, text, *gap, idx),
+            AnimationData::Sequence(s) => s.get(idx).map(|f| f.to_frame()).unwrap_or_default(),
+        }
+    }
+}
+
+/// How many times an animation is played
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Repeat {
+    /// Play the animation the given number of times, at least once
+    Times(u32),
+    /// Keep looping the animation until it is stopped
+    Forever,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat::Times(1)
+    }
+}
+
+/// Progress of an animation
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Progress {
+    /// Number of steps applied in the current iteration
+    pub step: usize,
+    /// Number of steps in one iteration
+    pub length: usize,
+    /// Number of completed iterations
+    pub iteration: u32,
+}
+
+impl Progress {
+    /// Percentage of the current iteration that has been applied
+    pub fn percent(&self) -> u8 {
+        if self.length == 0 {
+            return 100;
+        }
+        (self.step * 100 / self.length) as u8
+    }
+}
+
+/// An animation of a sequence of frames using a transition effect
+pub struct Animation<'a, const XSIZE: usize, const YSIZE: usize, const WORDS: usize = 1> {
+    frames: AnimationData<'a, XSIZE, YSIZE, WORDS>,
+    sequence: usize,
+    frame_index: usize,
+    index: usize,
+    length: usize,
+    effect: AnimationEffect,
+    wait: Duration,
+    next: Option<Instant>,
+    repeat: Repeat,
+    iteration: u32,
+    paused: bool,
+    stopped: bool,
+}
+
+/// Result of advancing an animation
+#[derive(PartialEq, Debug)]
+pub enum AnimationState<const XSIZE: usize, const YSIZE: usize> {
+    /// Keep displaying the current frame
+    Wait,
+    /// Display the provided frame
+    Apply(GreyscaleFrame<XSIZE, YSIZE>),
+    /// The animation is finished or stopped
+    Done,
+}
+
+impl<'a, const XSIZE: usize, const YSIZE: usize, const WORDS: usize> Animation<'a, XSIZE, YSIZE, WORDS> {
+    /// Create an animation of the frames with the effect, played once during the provided duration.
+    ///
+    /// Frame sequences are shown for the durations of their frames instead, see
+    /// [`sequence`](Self::sequence).
+    pub fn new(
+        frames: AnimationData<'a, XSIZE, YSIZE, WORDS>,
+        effect: AnimationEffect,
+        duration: Duration,
+    ) -> Result<Self, AnimationError> {
+        if frames.len() == 0 {
+            return Err(AnimationError::Empty);
+        }
+        let effect = match frames {
+            AnimationData::Text { .. } => AnimationEffect::None,
+            AnimationData::Sequence(s) if s.width() != XSIZE || s.height() != YSIZE => {
+                return Err(AnimationError::FrameSize)
+            }
+            AnimationData::Sequence(_) => AnimationEffect::None,
+            _ => effect,
+        };
+        let length = frames.len() * effect.steps::<XSIZE, YSIZE>();
+
+        if let Some(wait) = duration.checked_div(length as u32) {
+            Ok(Self {
+                frames,
+                frame_index: 0,
+                sequence: 0,
+                index: 0,
+                length,
+                effect,
+                wait,
+                next: None,
+                repeat: Repeat::default(),
+                iteration: 0,
+                paused: false,
+                stopped: false,
+            })
+        } else {
+            Err(AnimationError::TooFast)
+        }
+    }
+
+    /// Create an animation scrolling the text to the left at `speed` columns per second, using
+    /// the real width of each glyph of the font separated by `gap` empty columns.
+    ///
+    /// Text without any columns, such as an empty string, gives an animation that is already
+    /// finished. A speed of 0 is treated as 1 column per second.
+    pub fn scroll(text: &'a str, font: &'a dyn Font, gap: usize, speed: u32) -> Self {
+        let frames = AnimationData::Text { text, font, gap };
+        let length = frames.len();
+        Self {
+            frames,
+            frame_index: 0,
+            sequence: 0,
+            index: 0,
+            length,
+            effect: AnimationEffect::None,
+            wait: Duration::from_hz(u64::from(speed.max(1))),
+            next: None,
+            repeat: Repeat::default(),
+            iteration: 0,
+            paused: false,
+            stopped: length == 0,
+        }
+    }
+
+    /// Create an animation of the frame sequence, showing each frame for its own duration. The
+    /// frames must have the size of the display.
+    pub fn sequence(sequence: FrameSequence<'a>) -> Result<Self, AnimationError> {
+        Self::new(
+            AnimationData::Sequence(sequence),
+            AnimationEffect::None,
+            sequence.duration(),
+        )
+    }
+
+    fn current(&self) -> GreyscaleFrame<XSIZE, YSIZE> {
+        if let AnimationData::Sequence(s) = &self.frames {
+            return s.get(self.frame_index).map(|f| f.to_greyscale()).unwrap_or_default();
+        }
+
+        let mut current = self.frames.frame(self.frame_index);
+
+        let mut next = if self.frame_index < self.frames.len() - 1 {
+            self.frames.frame(self.frame_index + 1)
+        } else {
+            Frame::empty()
+        };
+
+        let s = self.sequence;
+        match self.effect {
+            AnimationEffect::None => {}
+            AnimationEffect::Slide => {
+                current.shift_left(s);
+                next.shift_right(XSIZE - s);
+                current.or(&next);
+            }
+            AnimationEffect::SlideRight => {
+                current.shift_right(s);
+                next.shift_left(XSIZE - s);
+                current.or(&next);
+            }
+            AnimationEffect::SlideUp => {
+                current.shift_up(s);
+                next.shift_down(YSIZE - s);
+                current.or(&next);
+            }
+            AnimationEffect::SlideDown => {
+                current.shift_down(s);
+                next.shift_up(YSIZE - s);
+                current.or(&next);
+            }
+            AnimationEffect::SlideUpLeft
+            | AnimationEffect::SlideUpRight
+            | AnimationEffect::SlideDownLeft
+            | AnimationEffect::SlideDownRight => {
+                // Both axes reach the edge on the same step, even if the display is not square
+                let steps = self.effect.steps::<XSIZE, YSIZE>();
+                let (dx, dy) = (s * XSIZE / steps, s * YSIZE / steps);
+                if matches!(
+                    self.effect,
+                    AnimationEffect::SlideUpLeft | AnimationEffect::SlideDownLeft
+                ) {
+                    current.shift_left(dx);
+                    next.shift_right(XSIZE - dx);
+                } else {
+                    current.shift_right(dx);
+                    next.shift_left(XSIZE - dx);
+                }
+                if matches!(
+                    self.effect,
+                    AnimationEffect::SlideUpLeft | AnimationEffect::SlideUpRight
+                ) {
+                    current.shift_up(dy);
+                    next.shift_down(YSIZE - dy);
+                } else {
+                    current.shift_down(dy);
+                    next.shift_up(YSIZE - dy);
+                }
+                current.or(&next);
+            }
+            AnimationEffect::Wipe => {
+                for y in 0..YSIZE {
+                    for x in 0..s {
+                        if next.is_set(x, y) {
+                            current.set(x, y);
+                        } else {
+                            current.unset(x, y);
+                        }
+                    }
+                }
+            }
+            AnimationEffect::Dissolve(seed) => {
+                let steps = self.effect.steps::<XSIZE, YSIZE>();
+                for y in 0..YSIZE {
+                    for x in 0..XSIZE {
+                        if dissolve_step(seed, y * XSIZE + x, steps) < s {
+                            if next.is_set(x, y) {
+                                current.set(x, y);
+                            } else {
+                                current.unset(x, y);
+                            }
+                        }
+                    }
+                }
+            }
+            AnimationEffect::Fade => {
+                let max = GreyscaleFrame::<XSIZE, YSIZE>::MAX_LEVEL as usize;
+                return if s < max {
+                    GreyscaleFrame::from_frame(&current, (max - s) as u8)
+                } else {
+                    GreyscaleFrame::from_frame(&next, (s - max) as u8)
+                };
+            }
+        }
+        current.into()
+    }
+
+    /// Set how many times the animation is played
+    pub fn set_repeat(&mut self, repeat: Repeat) {
+        self.repeat = repeat;
+    }
+
+    /// Pause the animation at the current frame
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume a paused animation, continuing with the next step
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.next = None;
+        }
+    }
+
+    /// Check if the animation is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stop the animation, leaving the last applied frame on the display
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    /// Check if the animation is stopped or has played all iterations
+    pub fn is_finished(&self) -> bool {
+        self.stopped
+    }
+
+    /// Restart the animation from the first frame
+    pub fn reset(&mut self) {
+        self.sequence = 0;
+        self.frame_index = 0;
+        self.index = 0;
+        self.iteration = 0;
+        self.next = None;
+        self.paused = false;
+        self.stopped = false;
+    }
+
+    // Forget when the next step is due, so it is applied on the next call instead of catching up
+    // on the time spent while the animation was not played
+    pub(crate) fn restart_clock(&mut self) {
+        self.next = None;
+    }
+
+    /// Return the progress of the animation
+    pub fn progress(&self) -> Progress {
+        Progress {
+            step: self.index,
+            length: self.length,
+            iteration: self.iteration,
+        }
+    }
+
+    // Start over for the next iteration if the animation is repeated
+    fn repeat(&mut self) -> bool {
+        let more = match self.repeat {
+            Repeat::Times(n) => self.iteration + 1 < n,
+            Repeat::Forever => true,
+        };
+        if more {
+            self.sequence = 0;
+            self.frame_index = 0;
+            self.index = 0;
+            self.iteration += 1;
+        }
+        more
+    }
+
+    /// Advance the animation to the provided point in time, returning the frame to display if
+    /// the next step is due
+    pub fn next(&mut self, now: Instant) -> AnimationState<XSIZE, YSIZE> {
+        if self.stopped {
+            return AnimationState::Done;
+        }
+        if self.paused {
+            return AnimationState::Wait;
+        }
+
+        let next = *self.next.get_or_insert(now);
+        if next <= now {
+            if self.index >= self.length && (self.length == 0 || !self.repeat()) {
+                self.stopped = true;
+                return AnimationState::Done;
+            }
+
+            let current = self.current();
+            let wait = match &self.frames {
+                AnimationData::Sequence(s) => s.get(self.frame_index).map_or(self.wait, |f| f.duration()),
+                _ => self.wait,
+            };
+            if self.sequence >= self.effect.steps::<XSIZE, YSIZE>() - 1 {
+                self.sequence = 0;
+                self.frame_index += 1;
+            } else {
+                self.sequence += 1;
+            }
+
+            self.index += 1;
+            self.next = Some(next + wait);
+            AnimationState::Apply(current)
+        } else {
+            AnimationState::Wait
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Errors produced when running animations
+pub enum AnimationError {
+    /// Animation scroll is too fast to keep up with the refresh rate
+    TooFast,
+    /// The frames of a sequence do not have the size of the display
+    FrameSize,
+    /// There are no frames to animate
+    Empty,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::fonts::{CHECK_MARK, CROSS_MARK, PENDOLINO};
+    use crate::display::{encode_sequence, Depth};
+
+    // Run the animation to completion, returning all frames applied
+    fn run<const WORDS: usize>(mut animation: Animation<'_, 5, 5, WORDS>) -> heapless::Vec<GreyscaleFrame<5, 5>, 64> {
+        let mut now = Instant::from_secs(1);
+        let mut frames = heapless::Vec::new();
+        loop {
+            match animation.next(now) {
+                AnimationState::Apply(f) => frames.push(f).unwrap(),
+                AnimationState::Wait => now += animation.wait,
+                AnimationState::Done => break,
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut animation: Animation<5, 5> = Animation::new(
+            AnimationData::Bytes(b"12"),
+            AnimationEffect::Slide,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+        let start = Instant::from_secs(1);
+        assert_eq!(
+            animation.next(start),
+            AnimationState::Apply(Frame::<5, 5>::from(b'1').into())
+        );
+        assert_eq!(animation.next(start), AnimationState::Wait);
+
+        let expected = animation.length;
+        let mut n = 1;
+        while n < expected {
+            if let AnimationState::Apply(_) = animation.next(start + animation.wait * n as u32) {
+                n += 1;
+            } else {
+                break;
+            }
+        }
+        assert_eq!(n, expected);
+        assert_eq!(animation.next(start + Duration::from_secs(1)), AnimationState::Done);
+    }
+
+    #[test]
+    fn test_animation_length() {
+        let animation: Animation<5, 5> = Animation::new(
+            AnimationData::Bytes(b"12"),
+            AnimationEffect::Slide,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        assert_eq!(animation.length, 10);
+
+        let animation: Animation<5, 5> = Animation::new(
+            AnimationData::Bytes(b"123"),
+            AnimationEffect::Slide,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        assert_eq!(animation.length, 15);
+
+        let animation: Animation<5, 5> = Animation::new(
+            AnimationData::Bytes(b"1234"),
+            AnimationEffect::Slide,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        assert_eq!(animation.length, 20);
+
+        let effects = [
+            (AnimationEffect::None, 2),
+            (AnimationEffect::SlideRight, 10),
+            (AnimationEffect::SlideUp, 10),
+            (AnimationEffect::SlideDown, 10),
+            (AnimationEffect::SlideUpLeft, 10),
+            (AnimationEffect::SlideDownRight, 10),
+            (AnimationEffect::Wipe, 10),
+            (AnimationEffect::Dissolve(7), 50),
+            (AnimationEffect::Fade, 36),
+        ];
+        for (effect, length) in effects {
+            let animation: Animation<5, 5> =
+                Animation::new(AnimationData::Bytes(b"12"), effect, Duration::from_secs(1)).unwrap();
+            assert_eq!(animation.length, length);
+            assert_eq!(run(animation).len(), length);
+        }
+    }
+
+    #[test]
+    fn test_animation_none() {
+        let frames = [CHECK_MARK, CROSS_MARK];
+        let animation = Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::None,
+            Duration::from_secs(1),
+        );
+        let applied = run(animation.unwrap());
+        assert_eq!(&applied[..], &[CHECK_MARK.into(), CROSS_MARK.into()]);
+    }
+
+    #[test]
+    fn test_animation_slide() {
+        let frames = [CHECK_MARK, CROSS_MARK];
+        for (effect, shift) in [
+            (AnimationEffect::Slide, Frame::shift_left as fn(&mut Frame<5, 5>, usize)),
+            (AnimationEffect::SlideRight, Frame::shift_right),
+            (AnimationEffect::SlideUp, Frame::shift_up),
+            (AnimationEffect::SlideDown, Frame::shift_down),
+        ] {
+            let applied = run(Animation::new(AnimationData::Frames(&frames), effect, Duration::from_secs(1)).unwrap());
+            assert_eq!(applied[0], CHECK_MARK.into());
+            assert_eq!(applied[5], CROSS_MARK.into());
+
+            // Halfway through the last frame, it is partly moved out of the display
+            let mut expected = CROSS_MARK;
+            shift(&mut expected, 2);
+            assert_eq!(applied[7], expected.into());
+        }
+
+        // Frames slide in from the opposite edge
+        let applied = run(Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::SlideUp,
+            Duration::from_secs(1),
+        )
+        .unwrap());
+        let mut expected = CHECK_MARK;
+        expected.shift_up(1);
+        let mut incoming = CROSS_MARK;
+        incoming.shift_down(4);
+        expected.or(&incoming);
+        assert_eq!(applied[1], expected.into());
+    }
+
+    #[test]
+    fn test_animation_slide_diagonal() {
+        let frames = [CHECK_MARK, CROSS_MARK];
+        type Shift = fn(&mut Frame<5, 5>, usize);
+        let effects: [(AnimationEffect, Shift, Shift); 4] = [
+            (AnimationEffect::SlideUpLeft, Frame::shift_left, Frame::shift_up),
+            (AnimationEffect::SlideUpRight, Frame::shift_right, Frame::shift_up),
+            (AnimationEffect::SlideDownLeft, Frame::shift_left, Frame::shift_down),
+            (AnimationEffect::SlideDownRight, Frame::shift_right, Frame::shift_down),
+        ];
+        for (effect, horizontal, vertical) in effects {
+            let applied = run(Animation::new(AnimationData::Frames(&frames), effect, Duration::from_secs(1)).unwrap());
+            assert_eq!(applied.len(), 10);
+            assert_eq!(applied[0], CHECK_MARK.into());
+            assert_eq!(applied[5], CROSS_MARK.into());
+
+            // Halfway through the last frame, it is moved out through the corner
+            let mut expected = CROSS_MARK;
+            horizontal(&mut expected, 2);
+            vertical(&mut expected, 2);
+            assert_eq!(applied[7], expected.into());
+        }
+
+        // The next frame slides in from the opposite corner
+        let applied = run(Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::SlideUpLeft,
+            Duration::from_secs(1),
+        )
+        .unwrap());
+        let mut expected = CHECK_MARK;
+        expected.shift_left(1);
+        expected.shift_up(1);
+        let mut incoming = CROSS_MARK;
+        incoming.shift_right(4);
+        incoming.shift_down(4);
+        expected.or(&incoming);
+        assert_eq!(applied[1], expected.into());
+    }
+
+    #[test]
+    fn test_animation_wipe() {
+        let frames = [CHECK_MARK, CROSS_MARK];
+        let applied = run(Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::Wipe,
+            Duration::from_secs(1),
+        )
+        .unwrap());
+        for (step, frame) in applied[..5].iter().enumerate() {
+            for y in 0..5 {
+                for x in 0..5 {
+                    let source = if x < step { CROSS_MARK } else { CHECK_MARK };
+                    assert_eq!(frame.is_set(x, y), source.is_set(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_animation_dissolve() {
+        let frames = [CHECK_MARK, CROSS_MARK];
+        let dissolve = |seed| {
+            run(Animation::new(
+                AnimationData::Frames(&frames),
+                AnimationEffect::Dissolve(seed),
+                Duration::from_secs(1),
+            )
+            .unwrap())
+        };
+        let applied = dissolve(1);
+        assert_eq!(applied[0], CHECK_MARK.into());
+        assert_eq!(applied[25], CROSS_MARK.into());
+
+        // Every pixel is either from the current or from the next frame
+        for frame in applied[..25].iter() {
+            for y in 0..5 {
+                for x in 0..5 {
+                    assert!(
+                        frame.is_set(x, y) == CHECK_MARK.is_set(x, y) || frame.is_set(x, y) == CROSS_MARK.is_set(x, y)
+                    );
+                }
+            }
+        }
+
+        // The same seed gives the same order
+        assert_eq!(applied, dissolve(1));
+        assert_ne!(applied, dissolve(2));
+    }
+
+    #[test]
+    fn test_animation_fade() {
+        let frames = [CHECK_MARK, CROSS_MARK];
+        let applied = run(Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::Fade,
+            Duration::from_secs(1),
+        )
+        .unwrap());
+        let max = GreyscaleFrame::<5, 5>::MAX_LEVEL;
+        assert_eq!(applied[0], GreyscaleFrame::from_frame(&CHECK_MARK, max));
+        assert_eq!(applied[4], GreyscaleFrame::from_frame(&CHECK_MARK, max - 4));
+        assert_eq!(applied[9], GreyscaleFrame::empty());
+        assert_eq!(applied[12], GreyscaleFrame::from_frame(&CROSS_MARK, 3));
+        assert_eq!(applied[18], GreyscaleFrame::from_frame(&CROSS_MARK, max));
+    }
+
+    #[test]
+    fn test_animation_pause() {
+        let frames = [CHECK_MARK, CROSS_MARK];
+        let mut animation = Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::None,
+            Duration::from_secs(2),
+        )
+        .unwrap();
+
+        let start = Instant::from_secs(1);
+        assert_eq!(animation.next(start), AnimationState::Apply(CHECK_MARK.into()));
+        animation.pause();
+        assert!(animation.is_paused());
+        assert_eq!(animation.next(start + Duration::from_secs(5)), AnimationState::Wait);
+        assert_eq!(animation.progress().step, 1);
+
+        // Continues with the next step when resumed
+        animation.resume();
+        let resumed = start + Duration::from_secs(10);
+        assert_eq!(animation.next(resumed), AnimationState::Apply(CROSS_MARK.into()));
+        assert_eq!(animation.next(resumed), AnimationState::Wait);
+        assert_eq!(animation.next(resumed + Duration::from_secs(1)), AnimationState::Done);
+        assert!(animation.is_finished());
+    }
+
+    #[test]
+    fn test_animation_restart_clock() {
+        let frames = [CHECK_MARK, CROSS_MARK, CHECK_MARK];
+        let mut animation = Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::None,
+            Duration::from_secs(3),
+        )
+        .unwrap();
+
+        let start = Instant::from_secs(1);
+        assert_eq!(animation.next(start), AnimationState::Apply(CHECK_MARK.into()));
+
+        // Played again much later, the steps missed in the meantime are not replayed at once
+        animation.restart_clock();
+        let restarted = start + Duration::from_secs(10);
+        assert_eq!(animation.next(restarted), AnimationState::Apply(CROSS_MARK.into()));
+        assert_eq!(animation.next(restarted), AnimationState::Wait);
+        assert_eq!(
+            animation.next(restarted + Duration::from_secs(1)),
+            AnimationState::Apply(CHECK_MARK.into())
+        );
+    }
+
+    #[test]
+    fn test_animation_stop() {
+        let mut animation: Animation<5, 5> = Animation::new(
+            AnimationData::Bytes(b"Hello"),
+            AnimationEffect::Slide,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        animation.set_repeat(Repeat::Forever);
+
+        let start = Instant::from_secs(1);
+        for n in 0..10 {
+            assert!(matches!(
+                animation.next(start + animation.wait * n),
+                AnimationState::Apply(_)
+            ));
+        }
+        let progress = animation.progress();
+        assert_eq!(progress.step, 10);
+        assert_eq!(progress.length, 25);
+        assert_eq!(progress.percent(), 40);
+
+        animation.stop();
+        assert!(animation.is_finished());
+        assert_eq!(animation.next(start + Duration::from_secs(10)), AnimationState::Done);
+
+        animation.reset();
+        assert_eq!(animation.progress().step, 0);
+        assert_eq!(
+            animation.next(start),
+            AnimationState::Apply(Frame::<5, 5>::from(b'H').into())
+        );
+    }
+
+    #[test]
+    fn test_animation_repeat() {
+        let frames = [CHECK_MARK, CROSS_MARK];
+        let mut animation = Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::None,
+            Duration::from_secs(2),
+        )
+        .unwrap();
+        animation.set_repeat(Repeat::Times(3));
+        let applied = run(animation);
+        assert_eq!(applied.len(), 6);
+        assert_eq!(applied[2], CHECK_MARK.into());
+        assert_eq!(applied[5], CROSS_MARK.into());
+
+        let mut animation = Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::None,
+            Duration::from_secs(2),
+        )
+        .unwrap();
+        animation.set_repeat(Repeat::Forever);
+        let start = Instant::from_secs(1);
+        for n in 0..100 {
+            assert!(matches!(
+                animation.next(start + animation.wait * n),
+                AnimationState::Apply(_)
+            ));
+        }
+        assert_eq!(animation.progress().iteration, 49);
+        assert!(!animation.is_finished());
+    }
+
+    #[test]
+    fn test_animation_scroll() {
+        let animation: Animation<5, 5> = Animation::scroll("Hi!", &PENDOLINO, 1, 10);
+        assert_eq!(animation.wait, Duration::from_millis(100));
+        assert_eq!(animation.length, PENDOLINO.text_width("Hi!", 1));
+
+        let applied = run(animation);
+        assert_eq!(applied.len(), 4 + 1 + 1 + 1 + 1);
+        assert_eq!(
+            applied[0],
+            fonts::render_text::<5, 5, 1>(&PENDOLINO, "Hi!", 1, 0).into()
+        );
+        assert_eq!(applied[5], fonts::render_text::<5, 5, 1>(&PENDOLINO, "i!", 1, 0).into());
+
+        // Effects do not apply to scrolled text
+        let animation: Animation<5, 5> = Animation::new(
+            AnimationData::Text {
+                text: "Hi!",
+                font: &PENDOLINO,
+                gap: 0,
+            },
+            AnimationEffect::Fade,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        assert_eq!(animation.length, 4 + 1 + 1);
+    }
+
+    #[test]
+    fn test_animation_scroll_empty() {
+        let mut animation: Animation<5, 5> = Animation::scroll("", &PENDOLINO, 1, 10);
+        assert!(animation.is_finished());
+        assert_eq!(animation.progress().percent(), 100);
+        assert_eq!(animation.next(Instant::from_secs(1)), AnimationState::Done);
+
+        // Nothing to show even when restarted and looping
+        animation.reset();
+        animation.set_repeat(Repeat::Forever);
+        assert_eq!(animation.next(Instant::from_secs(2)), AnimationState::Done);
+        assert!(animation.is_finished());
+    }
+
+    #[test]
+    fn test_animation_scroll_speed_zero() {
+        let mut animation: Animation<5, 5> = Animation::scroll("A", &PENDOLINO, 1, 0);
+        assert_eq!(animation.wait, Duration::from_secs(1));
+        assert!(matches!(
+            animation.next(Instant::from_secs(0)),
+            AnimationState::Apply(_)
+        ));
+        assert_eq!(animation.next(Instant::from_millis(999)), AnimationState::Wait);
+        assert!(matches!(
+            animation.next(Instant::from_secs(1)),
+            AnimationState::Apply(_)
+        ));
+    }
+
+    #[test]
+    fn test_sequence() {
+        let frames = [
+            (
+                GreyscaleFrame::from_image("00000:00000:00500:00000:00000"),
+                Duration::from_millis(100),
+            ),
+            (CROSS_MARK.into(), Duration::from_millis(300)),
+        ];
+        let mut data = [0; 64];
+        let len = encode_sequence(&frames, Depth::Greyscale, &mut data).unwrap();
+        let sequence = FrameSequence::parse(&data[..len]).unwrap();
+
+        let mut animation: Animation<5, 5> = Animation::sequence(sequence).unwrap();
+        let start = Instant::from_secs(1);
+        assert_eq!(animation.next(start), AnimationState::Apply(frames[0].0));
+        assert_eq!(animation.next(start + Duration::from_millis(99)), AnimationState::Wait);
+        assert_eq!(
+            animation.next(start + Duration::from_millis(100)),
+            AnimationState::Apply(frames[1].0)
+        );
+        assert_eq!(animation.next(start + Duration::from_millis(399)), AnimationState::Wait);
+        assert_eq!(animation.next(start + Duration::from_millis(400)), AnimationState::Done);
+
+        let animation: Result<Animation<4, 4>, _> = Animation::sequence(sequence);
+        assert!(matches!(animation, Err(AnimationError::FrameSize)));
+    }
+
+    #[test]
+    fn test_animation_empty() {
+        let frames: [Frame<5, 5>; 0] = [];
+        let animation = Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::Slide,
+            Duration::from_secs(1),
+        );
+        assert!(matches!(animation, Err(AnimationError::Empty)));
+
+        let animation: Result<Animation<5, 5>, _> =
+            Animation::new(AnimationData::Bytes(b""), AnimationEffect::None, Duration::from_secs(1));
+        assert!(matches!(animation, Err(AnimationError::Empty)));
+    }
+}
@@ -0,0 +1,308 @@
+//! Drawing primitives for frames
+//!
+//! Coordinates are signed so shapes may extend beyond the frame; points outside of the frame
+//! are clipped.
+use super::types::Frame;
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> Frame<XSIZE, YSIZE, WORDS> {
+    const fn contains(x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < XSIZE && (y as usize) < YSIZE
+    }
+
+    /// Enable (x, y) on this frame if it is within the frame
+    pub const fn plot(&mut self, x: i32, y: i32) {
+        if Self::contains(x, y) {
+            self.set(x as usize, y as usize);
+        }
+    }
+
+    /// Draw a line from (x0, y0) to (x1, y1)
+    pub const fn line(&mut self, mut x0: i32, mut y0: i32, x1: i32, y1: i32) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        loop {
+            self.plot(x0, y0);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+        }
+    }
+
+    /// Draw the outline of the rectangle with corners (x0, y0) and (x1, y1)
+    pub const fn rect(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        self.line(x0, y0, x1, y0);
+        self.line(x1, y0, x1, y1);
+        self.line(x1, y1, x0, y1);
+        self.line(x0, y1, x0, y0);
+    }
+
+    /// Draw a filled rectangle with corners (x0, y0) and (x1, y1)
+    pub const fn fill_rect(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let (top, bottom) = if y0 < y1 { (y0, y1) } else { (y1, y0) };
+        let mut y = top;
+        while y <= bottom {
+            self.line(x0, y, x1, y);
+            y += 1;
+        }
+    }
+
+    /// Draw the outline of a circle centered at (cx, cy)
+    pub const fn circle(&mut self, cx: i32, cy: i32, radius: i32) {
+        let mut x = radius;
+        let mut y = 0;
+        let mut err = 1 - radius;
+        while x >= y {
+            self.plot(cx + x, cy + y);
+            self.plot(cx + y, cy + x);
+            self.plot(cx - y, cy + x);
+            self.plot(cx - x, cy + y);
+            self.plot(cx - x, cy - y);
+            self.plot(cx - y, cy - x);
+            self.plot(cx + y, cy - x);
+            self.plot(cx + x, cy - y);
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Draw a filled circle centered at (cx, cy)
+    pub const fn fill_circle(&mut self, cx: i32, cy: i32, radius: i32) {
+        let mut x = radius;
+        let mut y = 0;
+        let mut err = 1 - radius;
+        while x >= y {
+            self.line(cx - x, cy + y, cx + x, cy + y);
+            self.line(cx - x, cy - y, cx + x, cy - y);
+            self.line(cx - y, cy + x, cx + y, cy + x);
+            self.line(cx - y, cy - x, cx + y, cy - x);
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Enable all points connected to (x, y) that are not set, stopping at set points.
+    pub const fn flood_fill(&mut self, x: i32, y: i32) {
+        if !Self::contains(x, y) || self.is_set(x as usize, y as usize) {
+            return;
+        }
+
+        // Grow the filled area one step at a time until it stops changing
+        let mut filled: Self = Self::empty();
+        filled.set(x as usize, y as usize);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            let mut py = 0;
+            while py < YSIZE {
+                let mut px = 0;
+                while px < XSIZE {
+                    if !filled.is_set(px, py)
+                        && !self.is_set(px, py)
+                        && ((px > 0 && filled.is_set(px - 1, py))
+                            || (px + 1 < XSIZE && filled.is_set(px + 1, py))
+                            || (py > 0 && filled.is_set(px, py - 1))
+                            || (py + 1 < YSIZE && filled.is_set(px, py + 1)))
+                    {
+                        filled.set(px, py);
+                        changed = true;
+                    }
+                    px += 1;
+                }
+                py += 1;
+            }
+        }
+        self.or(&filled);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::display::fonts::frame_5x5;
+    use crate::display::{bitmap_words, Frame};
+
+    const DIAGONAL: Frame<5, 5> = {
+        let mut frame = Frame::empty();
+        frame.line(0, 0, 4, 4);
+        frame
+    };
+
+    #[test]
+    fn test_line() {
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b10000,
+            0b01000,
+            0b00100,
+            0b00010,
+            0b00001,
+        ]);
+        assert_eq!(DIAGONAL, expected);
+
+        let mut frame: Frame<5, 5> = Frame::empty();
+        frame.line(4, 1, 0, 3);
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b00000,
+            0b00001,
+            0b00110,
+            0b11000,
+            0b00000,
+        ]);
+        assert_eq!(frame, expected);
+
+        // Clipped at the edges
+        let mut frame: Frame<5, 5> = Frame::empty();
+        frame.line(-2, 2, 10, 2);
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b00000,
+            0b00000,
+            0b11111,
+            0b00000,
+            0b00000,
+        ]);
+        assert_eq!(frame, expected);
+    }
+
+    #[test]
+    fn test_rect() {
+        let mut frame: Frame<5, 5> = Frame::empty();
+        frame.rect(0, 0, 4, 3);
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b11111,
+            0b10001,
+            0b10001,
+            0b11111,
+            0b00000,
+        ]);
+        assert_eq!(frame, expected);
+
+        let mut frame: Frame<5, 5> = Frame::empty();
+        frame.fill_rect(3, 4, 1, 2);
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b00000,
+            0b00000,
+            0b01110,
+            0b01110,
+            0b01110,
+        ]);
+        assert_eq!(frame, expected);
+    }
+
+    #[test]
+    fn test_circle() {
+        let mut frame: Frame<5, 5> = Frame::empty();
+        frame.circle(2, 2, 2);
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b01110,
+            0b10001,
+            0b10001,
+            0b10001,
+            0b01110,
+        ]);
+        assert_eq!(frame, expected);
+
+        let mut frame: Frame<5, 5> = Frame::empty();
+        frame.fill_circle(2, 2, 2);
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b01110,
+            0b11111,
+            0b11111,
+            0b11111,
+            0b01110,
+        ]);
+        assert_eq!(frame, expected);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        #[rustfmt::skip]
+        let mut frame: Frame<5, 5> = frame_5x5(&[
+            0b00100,
+            0b01010,
+            0b10001,
+            0b01010,
+            0b00100,
+        ]);
+        frame.flood_fill(2, 2);
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b00100,
+            0b01110,
+            0b11111,
+            0b01110,
+            0b00100,
+        ]);
+        assert_eq!(frame, expected);
+
+        // Filling a set point does nothing
+        let before = frame;
+        frame.flood_fill(0, 2);
+        assert_eq!(frame, before);
+
+        // The outside of the diamond is split into four corners
+        frame.flood_fill(0, 0);
+        #[rustfmt::skip]
+        let expected = frame_5x5(&[
+            0b11100,
+            0b11110,
+            0b11111,
+            0b01110,
+            0b00100,
+        ]);
+        assert_eq!(frame, expected);
+    }
+
+    #[test]
+    fn test_invert_xor() {
+        let mut frame = DIAGONAL;
+        frame.invert();
+        for y in 0..5 {
+            for x in 0..5 {
+                assert_eq!(frame.is_set(x, y), x != y);
+            }
+        }
+
+        frame.xor(&DIAGONAL);
+        let mut full: Frame<5, 5> = Frame::empty();
+        full.fill_rect(0, 0, 4, 4);
+        assert_eq!(frame, full);
+
+        frame.xor(&DIAGONAL);
+        frame.invert();
+        assert_eq!(frame, DIAGONAL);
+
+        // Inverting does not set bits beyond the frame width
+        let mut wide: Frame<33, 1, { bitmap_words(33) }> = Frame::empty();
+        wide.invert();
+        wide.shift_right(1);
+        assert!(!wide.is_set(0, 0));
+        assert!(wide.is_set(32, 0));
+    }
+}
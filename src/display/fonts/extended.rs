@@ -0,0 +1,104 @@
+//! Glyphs beyond the printable ASCII range of the pendolino font.
+//!
+//! Covers the Latin-1 letters and symbols used in European languages, arrows and a few icons
+//! matching the MicroPython image library. Accented letters are drawn with the accent on the
+//! top row and a 4 row high letter below it. The ring of 'Å' and 'å' and the circumflex of 'î'
+//! take two rows, so they can't be confused with the circumflex of the other letters.
+
+/// Number of extended glyphs
+pub const EXTENDED_COUNT: usize = 91;
+
+// Glyphs sorted by character, for binary search
+pub(super) const EXTENDED: [(char, [u8; 5]); EXTENDED_COUNT] = [
+    ('¡', [0b10000, 0b00000, 0b10000, 0b10000, 0b10000]),
+    ('£', [0b01100, 0b10000, 0b11100, 0b10000, 0b11110]),
+    ('°', [0b01000, 0b10100, 0b01000, 0b00000, 0b00000]),
+    ('±', [0b01000, 0b11100, 0b01000, 0b00000, 0b11100]),
+    ('µ', [0b00000, 0b10010, 0b10010, 0b11100, 0b10000]),
+    ('·', [0b00000, 0b00000, 0b10000, 0b00000, 0b00000]),
+    ('¿', [0b01000, 0b00000, 0b01000, 0b10000, 0b11100]),
+    ('À', [0b01000, 0b01100, 0b10010, 0b11110, 0b10010]),
+    ('Á', [0b00100, 0b01100, 0b10010, 0b11110, 0b10010]),
+    ('Â', [0b01100, 0b01100, 0b10010, 0b11110, 0b10010]),
+    ('Ã', [0b11110, 0b01100, 0b10010, 0b11110, 0b10010]),
+    ('Ä', [0b10010, 0b01100, 0b10010, 0b11110, 0b10010]),
+    ('Å', [0b01100, 0b10010, 0b01100, 0b11110, 0b10010]),
+    ('Æ', [0b01111, 0b10100, 0b11111, 0b10100, 0b10111]),
+    ('Ç', [0b01110, 0b10000, 0b10000, 0b01110, 0b00100]),
+    ('È', [0b01000, 0b11110, 0b11100, 0b10000, 0b11110]),
+    ('É', [0b00100, 0b11110, 0b11100, 0b10000, 0b11110]),
+    ('Ê', [0b01100, 0b11110, 0b11100, 0b10000, 0b11110]),
+    ('Ë', [0b10010, 0b11110, 0b11100, 0b10000, 0b11110]),
+    ('Ì', [0b10000, 0b11100, 0b01000, 0b01000, 0b11100]),
+    ('Í', [0b00100, 0b11100, 0b01000, 0b01000, 0b11100]),
+    ('Î', [0b01000, 0b11100, 0b01000, 0b01000, 0b11100]),
+    ('Ï', [0b10100, 0b11100, 0b01000, 0b01000, 0b11100]),
+    ('Ñ', [0b11110, 0b10010, 0b11010, 0b10110, 0b10010]),
+    ('Ò', [0b01000, 0b11110, 0b10010, 0b10010, 0b11110]),
+    ('Ó', [0b00100, 0b11110, 0b10010, 0b10010, 0b11110]),
+    ('Ô', [0b01100, 0b11110, 0b10010, 0b10010, 0b11110]),
+    ('Õ', [0b11110, 0b11110, 0b10010, 0b10010, 0b11110]),
+    ('Ö', [0b10010, 0b11110, 0b10010, 0b10010, 0b11110]),
+    ('×', [0b00000, 0b10100, 0b01000, 0b10100, 0b00000]),
+    ('Ø', [0b01111, 0b10011, 0b10101, 0b11001, 0b11110]),
+    ('Ù', [0b01000, 0b10010, 0b10010, 0b10010, 0b11110]),
+    ('Ú', [0b00100, 0b10010, 0b10010, 0b10010, 0b11110]),
+    ('Û', [0b01100, 0b10010, 0b10010, 0b10010, 0b11110]),
+    ('Ü', [0b10010, 0b10010, 0b10010, 0b10010, 0b11110]),
+    ('ß', [0b01100, 0b10010, 0b10100, 0b10010, 0b10100]),
+    ('à', [0b01000, 0b01110, 0b10010, 0b10010, 0b01110]),
+    ('á', [0b00100, 0b01110, 0b10010, 0b10010, 0b01110]),
+    ('â', [0b01100, 0b01110, 0b10010, 0b10010, 0b01110]),
+    ('ã', [0b11110, 0b01110, 0b10010, 0b10010, 0b01110]),
+    ('ä', [0b10010, 0b01110, 0b10010, 0b10010, 0b01110]),
+    ('å', [0b01100, 0b10010, 0b01110, 0b10010, 0b01110]),
+    ('æ', [0b00000, 0b11110, 0b01111, 0b10100, 0b11111]),
+    ('ç', [0b00000, 0b01110, 0b10000, 0b01110, 0b00100]),
+    ('è', [0b01000, 0b01100, 0b11110, 0b10000, 0b01110]),
+    ('é', [0b00100, 0b01100, 0b11110, 0b10000, 0b01110]),
+    ('ê', [0b01100, 0b01100, 0b11110, 0b10000, 0b01110]),
+    ('ë', [0b10010, 0b01100, 0b11110, 0b10000, 0b01110]),
+    ('ì', [0b10000, 0b01000, 0b01000, 0b01000, 0b01000]),
+    ('í', [0b00100, 0b01000, 0b01000, 0b01000, 0b01000]),
+    ('î', [0b01000, 0b10100, 0b01000, 0b01000, 0b01000]),
+    ('ï', [0b10100, 0b01000, 0b01000, 0b01000, 0b01000]),
+    ('ñ', [0b11110, 0b11100, 0b10010, 0b10010, 0b10010]),
+    ('ò', [0b01000, 0b01100, 0b10010, 0b10010, 0b01100]),
+    ('ó', [0b00100, 0b01100, 0b10010, 0b10010, 0b01100]),
+    ('ô', [0b01100, 0b01100, 0b10010, 0b10010, 0b01100]),
+    ('õ', [0b11110, 0b01100, 0b10010, 0b10010, 0b01100]),
+    ('ö', [0b10010, 0b01100, 0b10010, 0b10010, 0b01100]),
+    ('÷', [0b01000, 0b00000, 0b11100, 0b00000, 0b01000]),
+    ('ø', [0b00000, 0b01110, 0b10110, 0b11010, 0b11100]),
+    ('ù', [0b01000, 0b10010, 0b10010, 0b10010, 0b01110]),
+    ('ú', [0b00100, 0b10010, 0b10010, 0b10010, 0b01110]),
+    ('û', [0b01100, 0b10010, 0b10010, 0b10010, 0b01110]),
+    ('ü', [0b10010, 0b10010, 0b10010, 0b10010, 0b01110]),
+    ('ý', [0b00100, 0b10010, 0b01110, 0b00010, 0b01100]),
+    ('ÿ', [0b10010, 0b10010, 0b01110, 0b00010, 0b01100]),
+    ('€', [0b01110, 0b10000, 0b11100, 0b10000, 0b01110]),
+    ('←', [0b00100, 0b01000, 0b11111, 0b01000, 0b00100]),
+    ('↑', [0b00100, 0b01110, 0b10101, 0b00100, 0b00100]),
+    ('→', [0b00100, 0b00010, 0b11111, 0b00010, 0b00100]),
+    ('↓', [0b00100, 0b00100, 0b10101, 0b01110, 0b00100]),
+    ('↖', [0b11100, 0b11000, 0b10100, 0b00010, 0b00001]),
+    ('↗', [0b00111, 0b00011, 0b00101, 0b01000, 0b10000]),
+    ('↘', [0b10000, 0b01000, 0b00101, 0b00011, 0b00111]),
+    ('↙', [0b00001, 0b00010, 0b10100, 0b11000, 0b11100]),
+    ('⌂', [0b00100, 0b01110, 0b11111, 0b01110, 0b01010]),
+    ('■', [0b11111, 0b11111, 0b11111, 0b11111, 0b11111]),
+    ('□', [0b11111, 0b10001, 0b10001, 0b10001, 0b11111]),
+    ('☀', [0b10101, 0b01110, 0b11111, 0b01110, 0b10101]),
+    ('☂', [0b01110, 0b11111, 0b00100, 0b10100, 0b01100]),
+    ('★', [0b00100, 0b11111, 0b01110, 0b01010, 0b10001]),
+    ('☠', [0b01110, 0b10101, 0b11111, 0b01110, 0b01110]),
+    ('☹', [0b00000, 0b01010, 0b00000, 0b01110, 0b10001]),
+    ('☺', [0b00000, 0b01010, 0b00000, 0b10001, 0b01110]),
+    ('♡', [0b00000, 0b01010, 0b01110, 0b00100, 0b00000]),
+    ('♥', [0b01010, 0b11111, 0b11111, 0b01110, 0b00100]),
+    ('♦', [0b00100, 0b01010, 0b10001, 0b01010, 0b00100]),
+    ('♪', [0b00100, 0b00110, 0b00101, 0b11100, 0b11100]),
+    ('♫', [0b01111, 0b01001, 0b01001, 0b11011, 0b11011]),
+    ('✓', [0b00000, 0b00001, 0b00010, 0b10100, 0b01000]),
+    ('✗', [0b10001, 0b01010, 0b00100, 0b01010, 0b10001]),
+];
@@ -0,0 +1,158 @@
+use core::ops::RangeInclusive;
+
+use crate::display::Frame;
+
+/// A single character of a font, stored as rows of `width` bits with the most significant bit
+/// being the leftmost column.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Glyph {
+    rows: [u32; Glyph::MAX_HEIGHT],
+    width: usize,
+    height: usize,
+}
+
+impl Glyph {
+    /// Highest number of rows of a glyph
+    pub const MAX_HEIGHT: usize = 8;
+
+    /// Create a glyph from rows of `width` bits
+    pub const fn new(rows: &[u32], width: usize) -> Self {
+        assert!(rows.len() <= Self::MAX_HEIGHT);
+        assert!(width <= 32);
+        let mask = if width == 32 { u32::MAX } else { (1 << width) - 1 };
+        let mut glyph = Self {
+            rows: [0; Self::MAX_HEIGHT],
+            width,
+            height: rows.len(),
+        };
+        let mut y = 0;
+        while y < rows.len() {
+            glyph.rows[y] = rows[y] & mask;
+            y += 1;
+        }
+        glyph
+    }
+
+    /// Create an empty glyph of the provided size, e.g. for space
+    pub const fn blank(width: usize, height: usize) -> Self {
+        assert!(height <= Self::MAX_HEIGHT);
+        Self {
+            rows: [0; Self::MAX_HEIGHT],
+            width,
+            height,
+        }
+    }
+
+    /// Width in columns
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in rows
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Check if (x, y) is set in this glyph
+    pub const fn is_set(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.rows[y] & (1 << (self.width - 1 - x)) != 0
+    }
+}
+
+/// A font describing how characters are rendered on the display.
+///
+/// # Examples
+///
+/// A font with 3x5 digits that fits 3 characters on a 5x5 display when scrolling:
+///
+/// ```
+/// use core::ops::RangeInclusive;
+///
+/// use microbit_bsp::display::fonts::{Font, Glyph};
+///
+/// #[rustfmt::skip]
+/// const DIGITS: [[u32; 5]; 10] = [
+///     [0b111, 0b101, 0b101, 0b101, 0b111],
+///     [0b010, 0b110, 0b010, 0b010, 0b111],
+///     [0b111, 0b001, 0b111, 0b100, 0b111],
+///     [0b111, 0b001, 0b111, 0b001, 0b111],
+///     [0b101, 0b101, 0b111, 0b001, 0b001],
+///     [0b111, 0b100, 0b111, 0b001, 0b111],
+///     [0b111, 0b100, 0b111, 0b101, 0b111],
+///     [0b111, 0b001, 0b010, 0b010, 0b010],
+///     [0b111, 0b101, 0b111, 0b101, 0b111],
+///     [0b111, 0b101, 0b111, 0b001, 0b111],
+/// ];
+///
+/// struct Digits;
+///
+/// impl Font for Digits {
+///     fn height(&self) -> usize {
+///         5
+///     }
+///
+///     fn range(&self) -> RangeInclusive<char> {
+///         '0'..='9'
+///     }
+///
+///     fn glyph(&self, c: char) -> Option<Glyph> {
+///         let digit = c.to_digit(10)?;
+///         Some(Glyph::new(&DIGITS[digit as usize], 3))
+///     }
+/// }
+///
+/// assert_eq!(Digits.text_width("42", 1), 7);
+/// ```
+pub trait Font {
+    /// Height of the glyphs in rows
+    fn height(&self) -> usize;
+
+    /// First and last character covered by the font. Characters within the range may still
+    /// lack a glyph.
+    fn range(&self) -> RangeInclusive<char>;
+
+    /// Look up the glyph of the character, if the font has one
+    fn glyph(&self, c: char) -> Option<Glyph>;
+
+    /// Width in columns of the character, characters without a glyph are skipped
+    fn width(&self, c: char) -> usize {
+        self.glyph(c).map_or(0, |g| g.width())
+    }
+
+    /// Width in columns of the text when characters are separated by `gap` empty columns
+    fn text_width(&self, text: &str, gap: usize) -> usize {
+        text.chars()
+            .filter(|c| self.glyph(*c).is_some())
+            .map(|c| self.width(c) + gap)
+            .sum::<usize>()
+            .saturating_sub(gap)
+    }
+}
+
+/// Render the text in the font with proportional character widths, starting at column `offset`
+/// of the text. Characters without a glyph are skipped.
+pub fn render_text<const XSIZE: usize, const YSIZE: usize, const WORDS: usize>(
+    font: &dyn Font,
+    text: &str,
+    gap: usize,
+    offset: usize,
+) -> Frame<XSIZE, YSIZE, WORDS> {
+    let mut frame = Frame::empty();
+    let mut position = 0;
+    for glyph in text.chars().filter_map(|c| font.glyph(c)) {
+        if position >= offset + XSIZE {
+            break;
+        }
+        for y in 0..glyph.height().min(YSIZE) {
+            for column in 0..glyph.width() {
+                let x = position + column;
+                if glyph.is_set(column, y) && x >= offset && x < offset + XSIZE {
+                    frame.set(x - offset, y);
+                }
+            }
+        }
+        position += glyph.width() + gap;
+    }
+    frame
+}
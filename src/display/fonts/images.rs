@@ -0,0 +1,688 @@
+//! The standard images of the MicroPython and MakeCode micro:bit runtimes
+use super::frame_5x5;
+use crate::display::Frame;
+
+#[rustfmt::skip]
+/// A heart
+pub const HEART: Frame<5, 5> = frame_5x5(&[
+    0b01010,
+    0b11111,
+    0b11111,
+    0b01110,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// A small heart
+pub const HEART_SMALL: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b01010,
+    0b01110,
+    0b00100,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A happy face
+pub const HAPPY: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b01010,
+    0b00000,
+    0b10001,
+    0b01110,
+]);
+
+#[rustfmt::skip]
+/// A smile
+pub const SMILE: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00000,
+    0b00000,
+    0b10001,
+    0b01110,
+]);
+
+#[rustfmt::skip]
+/// A sad face
+pub const SAD: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b01010,
+    0b00000,
+    0b01110,
+    0b10001,
+]);
+
+#[rustfmt::skip]
+/// A confused face
+pub const CONFUSED: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b01010,
+    0b00000,
+    0b01010,
+    0b10101,
+]);
+
+#[rustfmt::skip]
+/// An angry face
+pub const ANGRY: Frame<5, 5> = frame_5x5(&[
+    0b10001,
+    0b01010,
+    0b00000,
+    0b11111,
+    0b10101,
+]);
+
+#[rustfmt::skip]
+/// A sleeping face
+pub const ASLEEP: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b11011,
+    0b00000,
+    0b01110,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A surprised face
+pub const SURPRISED: Frame<5, 5> = frame_5x5(&[
+    0b01010,
+    0b00000,
+    0b00100,
+    0b01010,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// A silly face
+pub const SILLY: Frame<5, 5> = frame_5x5(&[
+    0b10001,
+    0b00000,
+    0b11111,
+    0b00101,
+    0b00111,
+]);
+
+#[rustfmt::skip]
+/// A fabulous face with sunglasses
+pub const FABULOUS: Frame<5, 5> = frame_5x5(&[
+    0b11111,
+    0b11011,
+    0b00000,
+    0b01010,
+    0b01110,
+]);
+
+#[rustfmt::skip]
+/// An unimpressed face
+pub const MEH: Frame<5, 5> = frame_5x5(&[
+    0b01010,
+    0b00000,
+    0b00010,
+    0b00100,
+    0b01000,
+]);
+
+#[rustfmt::skip]
+/// A tick
+pub const YES: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00001,
+    0b00010,
+    0b10100,
+    0b01000,
+]);
+
+#[rustfmt::skip]
+/// A cross
+pub const NO: Frame<5, 5> = frame_5x5(&[
+    0b10001,
+    0b01010,
+    0b00100,
+    0b01010,
+    0b10001,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 12 o'clock
+pub const CLOCK12: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b00100,
+    0b00100,
+    0b00000,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 1 o'clock
+pub const CLOCK1: Frame<5, 5> = frame_5x5(&[
+    0b00010,
+    0b00010,
+    0b00100,
+    0b00000,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 2 o'clock
+pub const CLOCK2: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00011,
+    0b00100,
+    0b00000,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 3 o'clock
+pub const CLOCK3: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00000,
+    0b00111,
+    0b00000,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 4 o'clock
+pub const CLOCK4: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00000,
+    0b00100,
+    0b00011,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 5 o'clock
+pub const CLOCK5: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00000,
+    0b00100,
+    0b00010,
+    0b00010,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 6 o'clock
+pub const CLOCK6: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00000,
+    0b00100,
+    0b00100,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 7 o'clock
+pub const CLOCK7: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00000,
+    0b00100,
+    0b01000,
+    0b01000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 8 o'clock
+pub const CLOCK8: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00000,
+    0b00100,
+    0b11000,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 9 o'clock
+pub const CLOCK9: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00000,
+    0b11100,
+    0b00000,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 10 o'clock
+pub const CLOCK10: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b11000,
+    0b00100,
+    0b00000,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A clock pointing at 11 o'clock
+pub const CLOCK11: Frame<5, 5> = frame_5x5(&[
+    0b01000,
+    0b01000,
+    0b00100,
+    0b00000,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// An arrow pointing north
+pub const ARROW_N: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b01110,
+    0b10101,
+    0b00100,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// An arrow pointing north east
+pub const ARROW_NE: Frame<5, 5> = frame_5x5(&[
+    0b00111,
+    0b00011,
+    0b00101,
+    0b01000,
+    0b10000,
+]);
+
+#[rustfmt::skip]
+/// An arrow pointing east
+pub const ARROW_E: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b00010,
+    0b11111,
+    0b00010,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// An arrow pointing south east
+pub const ARROW_SE: Frame<5, 5> = frame_5x5(&[
+    0b10000,
+    0b01000,
+    0b00101,
+    0b00011,
+    0b00111,
+]);
+
+#[rustfmt::skip]
+/// An arrow pointing south
+pub const ARROW_S: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b00100,
+    0b10101,
+    0b01110,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// An arrow pointing south west
+pub const ARROW_SW: Frame<5, 5> = frame_5x5(&[
+    0b00001,
+    0b00010,
+    0b10100,
+    0b11000,
+    0b11100,
+]);
+
+#[rustfmt::skip]
+/// An arrow pointing west
+pub const ARROW_W: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b01000,
+    0b11111,
+    0b01000,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// An arrow pointing north west
+pub const ARROW_NW: Frame<5, 5> = frame_5x5(&[
+    0b11100,
+    0b11000,
+    0b10100,
+    0b00010,
+    0b00001,
+]);
+
+#[rustfmt::skip]
+/// A triangle pointing up
+pub const TRIANGLE: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00100,
+    0b01010,
+    0b11111,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A triangle in the left corner
+pub const TRIANGLE_LEFT: Frame<5, 5> = frame_5x5(&[
+    0b10000,
+    0b11000,
+    0b10100,
+    0b10010,
+    0b11111,
+]);
+
+#[rustfmt::skip]
+/// Alternating LEDs in a chessboard pattern
+pub const CHESSBOARD: Frame<5, 5> = frame_5x5(&[
+    0b01010,
+    0b10101,
+    0b01010,
+    0b10101,
+    0b01010,
+]);
+
+#[rustfmt::skip]
+/// A diamond
+pub const DIAMOND: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b01010,
+    0b10001,
+    0b01010,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// A small diamond
+pub const DIAMOND_SMALL: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b00100,
+    0b01010,
+    0b00100,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A square
+pub const SQUARE: Frame<5, 5> = frame_5x5(&[
+    0b11111,
+    0b10001,
+    0b10001,
+    0b10001,
+    0b11111,
+]);
+
+#[rustfmt::skip]
+/// A small square
+pub const SQUARE_SMALL: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b01110,
+    0b01010,
+    0b01110,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A rabbit
+pub const RABBIT: Frame<5, 5> = frame_5x5(&[
+    0b10100,
+    0b10100,
+    0b11110,
+    0b11010,
+    0b11110,
+]);
+
+#[rustfmt::skip]
+/// A cow
+pub const COW: Frame<5, 5> = frame_5x5(&[
+    0b10001,
+    0b10001,
+    0b11111,
+    0b01110,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// A crotchet note
+pub const MUSIC_CROTCHET: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b00100,
+    0b00100,
+    0b11100,
+    0b11100,
+]);
+
+#[rustfmt::skip]
+/// A quaver note
+pub const MUSIC_QUAVER: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b00110,
+    0b00101,
+    0b11100,
+    0b11100,
+]);
+
+#[rustfmt::skip]
+/// A pair of quaver notes
+pub const MUSIC_QUAVERS: Frame<5, 5> = frame_5x5(&[
+    0b01111,
+    0b01001,
+    0b01001,
+    0b11011,
+    0b11011,
+]);
+
+#[rustfmt::skip]
+/// A pitchfork
+pub const PITCHFORK: Frame<5, 5> = frame_5x5(&[
+    0b10101,
+    0b10101,
+    0b11111,
+    0b00100,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// A christmas tree
+pub const XMAS: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b01110,
+    0b00100,
+    0b01110,
+    0b11111,
+]);
+
+#[rustfmt::skip]
+/// Pac-Man
+pub const PACMAN: Frame<5, 5> = frame_5x5(&[
+    0b01111,
+    0b11010,
+    0b11100,
+    0b11110,
+    0b01111,
+]);
+
+#[rustfmt::skip]
+/// A target
+pub const TARGET: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b01110,
+    0b11011,
+    0b01110,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// A t-shirt
+pub const TSHIRT: Frame<5, 5> = frame_5x5(&[
+    0b11011,
+    0b11111,
+    0b01110,
+    0b01110,
+    0b01110,
+]);
+
+#[rustfmt::skip]
+/// A rollerskate
+pub const ROLLERSKATE: Frame<5, 5> = frame_5x5(&[
+    0b00011,
+    0b00011,
+    0b11111,
+    0b11111,
+    0b01010,
+]);
+
+#[rustfmt::skip]
+/// A duck
+pub const DUCK: Frame<5, 5> = frame_5x5(&[
+    0b01100,
+    0b11100,
+    0b01111,
+    0b01110,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A house
+pub const HOUSE: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b01110,
+    0b11111,
+    0b01110,
+    0b01010,
+]);
+
+#[rustfmt::skip]
+/// A tortoise
+pub const TORTOISE: Frame<5, 5> = frame_5x5(&[
+    0b00000,
+    0b01110,
+    0b11111,
+    0b01010,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A butterfly
+pub const BUTTERFLY: Frame<5, 5> = frame_5x5(&[
+    0b11011,
+    0b11111,
+    0b00100,
+    0b11111,
+    0b11011,
+]);
+
+#[rustfmt::skip]
+/// A stick figure
+pub const STICKFIGURE: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b11111,
+    0b00100,
+    0b01010,
+    0b10001,
+]);
+
+#[rustfmt::skip]
+/// A ghost
+pub const GHOST: Frame<5, 5> = frame_5x5(&[
+    0b11111,
+    0b10101,
+    0b11111,
+    0b11111,
+    0b10101,
+]);
+
+#[rustfmt::skip]
+/// A sword
+pub const SWORD: Frame<5, 5> = frame_5x5(&[
+    0b00100,
+    0b00100,
+    0b00100,
+    0b01110,
+    0b00100,
+]);
+
+#[rustfmt::skip]
+/// A giraffe
+pub const GIRAFFE: Frame<5, 5> = frame_5x5(&[
+    0b11000,
+    0b01000,
+    0b01000,
+    0b01110,
+    0b01010,
+]);
+
+#[rustfmt::skip]
+/// A skull
+pub const SKULL: Frame<5, 5> = frame_5x5(&[
+    0b01110,
+    0b10101,
+    0b11111,
+    0b01110,
+    0b01110,
+]);
+
+#[rustfmt::skip]
+/// An umbrella
+pub const UMBRELLA: Frame<5, 5> = frame_5x5(&[
+    0b01110,
+    0b11111,
+    0b00100,
+    0b10100,
+    0b01100,
+]);
+
+#[rustfmt::skip]
+/// A snake
+pub const SNAKE: Frame<5, 5> = frame_5x5(&[
+    0b11000,
+    0b11011,
+    0b01010,
+    0b01110,
+    0b00000,
+]);
+
+#[rustfmt::skip]
+/// A pair of scissors
+pub const SCISSORS: Frame<5, 5> = frame_5x5(&[
+    0b11001,
+    0b11010,
+    0b00100,
+    0b11010,
+    0b11001,
+]);
+
+/// All clock images, starting at 12 o'clock and moving clockwise
+pub const ALL_CLOCKS: [Frame<5, 5>; 12] = [
+    CLOCK12, CLOCK1, CLOCK2, CLOCK3, CLOCK4, CLOCK5, CLOCK6, CLOCK7, CLOCK8, CLOCK9, CLOCK10, CLOCK11,
+];
+
+/// All arrow images, starting at north and moving clockwise
+pub const ALL_ARROWS: [Frame<5, 5>; 8] = [
+    ARROW_N, ARROW_NE, ARROW_E, ARROW_SE, ARROW_S, ARROW_SW, ARROW_W, ARROW_NW,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::fonts::{ARROW_LEFT, ARROW_RIGHT, CHECK_MARK};
+
+    #[test]
+    fn test_arrows() {
+        assert_eq!(ARROW_E, ARROW_RIGHT);
+        assert_eq!(ARROW_W, ARROW_LEFT);
+        for i in 0..ALL_ARROWS.len() - 2 {
+            assert_eq!(ALL_ARROWS[i].rotate_cw(), ALL_ARROWS[i + 2]);
+        }
+    }
+
+    #[test]
+    fn test_clocks() {
+        for clock in ALL_CLOCKS.iter() {
+            // The hand starts at the center
+            assert!(clock.is_set(2, 2));
+            let lit = (0..25).filter(|i| clock.is_set(i % 5, i / 5)).count();
+            assert_eq!(lit, 3);
+        }
+        assert_eq!(CLOCK12.rotate_cw(), CLOCK3);
+        assert_eq!(CLOCK3.rotate_cw(), CLOCK6);
+    }
+
+    #[test]
+    fn test_images() {
+        assert_eq!(YES, CHECK_MARK);
+        assert_eq!(HEART.rotate_cw().rotate_ccw(), HEART);
+        assert!(HEART_SMALL.is_set(2, 3));
+        assert!(!HEART_SMALL.is_set(2, 4));
+    }
+}
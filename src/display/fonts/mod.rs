@@ -1,8 +1,16 @@
 //! Bitmaps and fonts for the micro:bit
 
+use core::ops::RangeInclusive;
+
 use super::types::*;
+mod extended;
+mod font;
+mod images;
 mod pendolino;
 
+pub use font::*;
+pub use images::*;
+
 mod bitmaps {
     use super::*;
 
@@ -47,49 +55,126 @@ mod bitmaps {
     ]);
 
     /// Construct a 5x5 frame from a byte slice
-    pub const fn frame_5x5<const XSIZE: usize, const YSIZE: usize>(input: &[u8; 5]) -> Frame<XSIZE, YSIZE> {
+    pub const fn frame_5x5<const XSIZE: usize, const YSIZE: usize, const WORDS: usize>(
+        input: &[u8; 5],
+    ) -> Frame<XSIZE, YSIZE, WORDS> {
         //assert!(XSIZE == 5);
         //assert!(YSIZE == 5);
         let mut data = [Bitmap::empty(5); YSIZE];
-        data[0] = Bitmap::new(input[0], 5);
-        data[1] = Bitmap::new(input[1], 5);
-        data[2] = Bitmap::new(input[2], 5);
-        data[3] = Bitmap::new(input[3], 5);
-        data[4] = Bitmap::new(input[4], 5);
+        data[0] = Bitmap::new(input[0] as u32, 5);
+        data[1] = Bitmap::new(input[1] as u32, 5);
+        data[2] = Bitmap::new(input[2] as u32, 5);
+        data[3] = Bitmap::new(input[3] as u32, 5);
+        data[4] = Bitmap::new(input[4] as u32, 5);
         Frame::new(data)
     }
 }
 
 pub use bitmaps::*;
 
-impl<const XSIZE: usize, const YSIZE: usize> Into<Frame<XSIZE, YSIZE>> for u8 {
-    fn into(self) -> Frame<XSIZE, YSIZE> {
-        (self as char).into()
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> From<u8> for Frame<XSIZE, YSIZE, WORDS> {
+    fn from(c: u8) -> Self {
+        (c as char).into()
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> From<char> for Frame<XSIZE, YSIZE, WORDS> {
+    fn from(c: char) -> Self {
+        PENDOLINO.frame(c)
     }
 }
 
-impl<const XSIZE: usize, const YSIZE: usize> Into<Frame<XSIZE, YSIZE>> for char {
-    fn into(self) -> Frame<XSIZE, YSIZE> {
+/// Width of a glyph without any set columns, e.g. space
+const BLANK_WIDTH: usize = 3;
+
+/// The default font, with blank fallback glyphs
+pub const PENDOLINO: Pendolino = Pendolino::new();
+
+/// The pendolino font of the micro:bit runtime, extended with Latin-1 letters, symbols, arrows
+/// and icons.
+///
+/// Glyphs are 5 rows of 5 bits, the most significant bit being the leftmost column. Characters
+/// without a glyph are rendered using the fallback glyph.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Pendolino {
+    fallback: [u8; 5],
+}
+
+impl Pendolino {
+    /// Create the font with a blank fallback glyph
+    pub const fn new() -> Self {
+        Self::with_fallback([0; 5])
+    }
+
+    /// Create the font with the provided fallback glyph for unsupported characters
+    pub const fn with_fallback(fallback: [u8; 5]) -> Self {
+        Self { fallback }
+    }
+
+    // Rows of the glyph for the character, or the fallback glyph if the font has none
+    fn bitmap(&self, c: char) -> [u8; 5] {
+        let n = c as usize;
+        if n == pendolino::PRINTABLE_START {
+            [0; 5]
+        } else if n > pendolino::PRINTABLE_START && n < pendolino::PRINTABLE_START + pendolino::PRINTABLE_COUNT {
+            pendolino::PENDOLINO3[n - pendolino::PRINTABLE_START]
+        } else if let Ok(i) = extended::EXTENDED.binary_search_by_key(&c, |(k, _)| *k) {
+            extended::EXTENDED[i].1
+        } else {
+            self.fallback
+        }
+    }
+
+    /// Create a 5x5 frame showing the character
+    pub fn frame<const XSIZE: usize, const YSIZE: usize, const WORDS: usize>(
+        &self,
+        c: char,
+    ) -> Frame<XSIZE, YSIZE, WORDS> {
         assert!(XSIZE == 5);
         assert!(YSIZE == 5);
 
-        let n = self as usize;
-        if n > pendolino::PRINTABLE_START && n < pendolino::PRINTABLE_START + pendolino::PRINTABLE_COUNT {
-            frame_5x5(&pendolino::PENDOLINO3[n - pendolino::PRINTABLE_START])
-        } else {
-            frame_5x5(&[0, 0, 0, 0, 0])
+        frame_5x5(&self.bitmap(c))
+    }
+}
+
+impl Default for Pendolino {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Font for Pendolino {
+    fn height(&self) -> usize {
+        5
+    }
+
+    fn range(&self) -> RangeInclusive<char> {
+        ' '..=extended::EXTENDED[extended::EXTENDED_COUNT - 1].0
+    }
+
+    /// Glyph of the character without empty columns on either side, or the fallback glyph
+    fn glyph(&self, c: char) -> Option<Glyph> {
+        let bitmap = self.bitmap(c);
+        let columns = bitmap.iter().fold(0, |acc, row| acc | (row & 0x1f) as u32);
+        if columns == 0 {
+            return Some(Glyph::blank(BLANK_WIDTH, 5));
         }
+
+        // Drop the empty columns on the right, the mask of the glyph drops them on the left
+        let last = columns.trailing_zeros();
+        let width = (32 - columns.leading_zeros() - last) as usize;
+        Some(Glyph::new(&bitmap.map(|row| (row as u32 & 0x1f) >> last), width))
     }
 }
 
-/*
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_to_frame() {
-        let frame: Frame<5, 5> = 'D'.to_frame();
+        let frame: Frame<5, 5> = 'D'.into();
 
         assert!(frame.is_set(0, 0));
         assert!(frame.is_set(1, 0));
@@ -121,5 +206,128 @@ mod tests {
         assert!(!frame.is_set(3, 4));
         assert!(!frame.is_set(4, 4));
     }
+
+    #[test]
+    fn test_char_width() {
+        assert_eq!(PENDOLINO.width('D'), 4);
+        assert_eq!(PENDOLINO.width('i'), 1);
+        assert_eq!(PENDOLINO.width('.'), 1);
+        assert_eq!(PENDOLINO.width('M'), 5);
+        assert_eq!(PENDOLINO.width(' '), BLANK_WIDTH);
+
+        assert_eq!(PENDOLINO.text_width("", 1), 0);
+        assert_eq!(PENDOLINO.text_width("i", 1), 1);
+        assert_eq!(PENDOLINO.text_width("Hi", 1), 4 + 1 + 1);
+        assert_eq!(PENDOLINO.text_width("Hi", 0), 4 + 1);
+    }
+
+    #[test]
+    fn test_render_text() {
+        // Narrow glyphs are packed next to each other
+        let frame: Frame<5, 5> = render_text(&PENDOLINO, "i.i", 1, 0);
+        for x in 0..5 {
+            assert_eq!(frame.is_set(x, 3), x % 2 == 0);
+        }
+
+        // Rendering from an offset shifts the text left
+        let mut expected: Frame<8, 5> = render_text(&PENDOLINO, "Hi", 1, 0);
+        expected.shift_left(2);
+        assert_eq!(render_text::<8, 5, 1>(&PENDOLINO, "Hi", 1, 2), expected);
+
+        let frame: Frame<5, 5> = render_text(&PENDOLINO, "Hi", 1, 5);
+        assert!(frame.is_set(0, 4));
+        assert!(!frame.is_set(1, 4));
+    }
+
+    #[test]
+    fn test_extended_glyphs() {
+        assert!(extended::EXTENDED.windows(2).all(|w| w[0].0 < w[1].0));
+
+        // Every glyph is distinct, so no two accents look the same
+        for (i, (c, glyph)) in extended::EXTENDED.iter().enumerate() {
+            for (other, g) in &extended::EXTENDED[i + 1..] {
+                assert_ne!(glyph, g, "{} and {} share a glyph", c, other);
+            }
+        }
+
+        // Accented letters have the accent on the top row
+        let frame: Frame<5, 5> = 'é'.into();
+        assert!(frame.is_set(2, 0));
+        assert!(!frame.is_set(1, 0));
+        assert!(frame.is_set(1, 0) != Frame::<5, 5>::from('è').is_set(1, 0));
+        assert_eq!(PENDOLINO.width('é'), 4);
+
+        // Multi-byte characters are decoded
+        assert_eq!(PENDOLINO.text_width("20°C", 1), 4 + 1 + 4 + 1 + 3 + 1 + 4);
+        assert_eq!(Frame::<5, 5>::from('←'), ARROW_LEFT);
+        assert_eq!(Frame::<5, 5>::from('→'), ARROW_RIGHT);
+        assert_eq!(Frame::<5, 5>::from(0xe9u8), Frame::<5, 5>::from('é'));
+    }
+
+    #[test]
+    fn test_glyph() {
+        let glyph = PENDOLINO.glyph('!').unwrap();
+        assert_eq!(glyph.width(), 1);
+        assert_eq!(glyph.height(), 5);
+        assert!(glyph.is_set(0, 0));
+        assert!(!glyph.is_set(0, 3));
+        assert!(!glyph.is_set(1, 0));
+
+        let glyph = PENDOLINO.glyph('D').unwrap();
+        assert_eq!(glyph, Glyph::new(&[0b1110, 0b1001, 0b1001, 0b1001, 0b1110], 4));
+
+        assert!(PENDOLINO.range().contains(&'A'));
+        assert!(PENDOLINO.range().contains(&'♥'));
+        assert!(!PENDOLINO.range().contains(&'\n'));
+    }
+
+    // Digits only font, all other characters are skipped
+    struct Digits;
+
+    impl Font for Digits {
+        fn height(&self) -> usize {
+            3
+        }
+
+        fn range(&self) -> RangeInclusive<char> {
+            '0'..='9'
+        }
+
+        fn glyph(&self, c: char) -> Option<Glyph> {
+            let digit = c.to_digit(10)?;
+            Some(Glyph::new(&[digit, digit, digit], 4))
+        }
+    }
+
+    #[test]
+    fn test_custom_font() {
+        assert_eq!(Digits.width('a'), 0);
+        assert_eq!(Digits.text_width("1a2", 1), 9);
+
+        let frame: Frame<5, 5> = render_text(&Digits, "1a2", 1, 3);
+        for y in 0..5 {
+            for x in 0..5 {
+                // Last column of '1', the gap, then the first columns of '2'
+                assert_eq!(frame.is_set(x, y), (x == 0 || x == 4) && y < 3);
+            }
+        }
+        let frame: Frame<5, 5> = render_text(&Digits, "1a2", 1, 5);
+        assert!(frame.is_set(2, 2));
+        assert!(!frame.is_set(2, 3));
+    }
+
+    #[test]
+    fn test_fallback_glyph() {
+        let frame: Frame<5, 5> = '\u{1F600}'.into();
+        assert_eq!(frame, Frame::empty());
+
+        let font = Pendolino::with_fallback([0b11111, 0b10001, 0b10001, 0b10001, 0b11111]);
+        let frame: Frame<5, 5> = font.frame('\u{1F600}');
+        assert!(frame.is_set(0, 0));
+        assert!(!frame.is_set(2, 2));
+        assert_eq!(font.width('\u{1F600}'), 5);
+
+        // Space is blank rather than the fallback
+        assert_eq!(font.frame::<5, 5, 1>(' '), Frame::empty());
+    }
 }
-*/
@@ -0,0 +1,607 @@
+//! Sprites, layers and a game loop for simple games, modelled after MakeCode's `game` extension
+//!
+//! A [`Game`] is advanced one tick at a time by an update function, which moves the [`Sprite`]s
+//! and draws them onto [`Layer`]s. The layers are combined into the frame shown on the display,
+//! and can blink independently, e.g. to highlight a target. The game runs until the update
+//! function ends it with [`Game::game_over`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use microbit_bsp::display::game::{Direction, Game, Sprite};
+//! use microbit_bsp::embassy_time::Duration;
+//! use microbit_bsp::Microbit;
+//!
+//! # async fn dodge() {
+//! let mut display = Microbit::default().display;
+//! let mut game: Game<5, 5, 1> = Game::new(Duration::from_millis(200));
+//! let player = Sprite::new(2, 4);
+//! let mut rock = Sprite::new(0, 0);
+//! rock.set_direction(Direction::Down);
+//!
+//! display
+//!     .play_game(&mut game, |game| {
+//!         rock.move_by(1);
+//!         if rock.is_touching(&player) {
+//!             game.game_over();
+//!         } else if rock.y() == 4 {
+//!             game.add_score(1);
+//!             rock.set_position((rock.x() + 2) % 5, 0);
+//!         }
+//!
+//!         let layer = game.layer_mut(0);
+//!         layer.clear();
+//!         layer.draw(&player);
+//!         layer.draw(&rock);
+//!     })
+//!     .await;
+//! # }
+//! ```
+use embassy_time::{Duration, Instant};
+use embedded_hal::digital::OutputPin;
+
+use super::{Frame, LedMatrix};
+
+/// Direction of a sprite, in steps of 45 degrees
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// Towards the top row
+    Up,
+    /// Towards the top right corner
+    UpRight,
+    /// Towards the right column
+    Right,
+    /// Towards the bottom right corner
+    DownRight,
+    /// Towards the bottom row
+    Down,
+    /// Towards the bottom left corner
+    DownLeft,
+    /// Towards the left column
+    Left,
+    /// Towards the top left corner
+    UpLeft,
+}
+
+impl Direction {
+    const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    /// Create the direction closest to the angle in degrees, with 0 being up and 90 being right
+    /// as in MakeCode
+    pub fn from_degrees(degrees: i32) -> Self {
+        let step = (degrees.rem_euclid(360) + 22) / 45;
+        Self::ALL[step as usize % 8]
+    }
+
+    /// Angle of the direction in degrees between -135 and 180, with 0 being up and 90 being right
+    pub fn degrees(&self) -> i32 {
+        let degrees = *self as i32 * 45;
+        if degrees > 180 {
+            degrees - 360
+        } else {
+            degrees
+        }
+    }
+
+    /// Turn clockwise by the number of 45 degree steps
+    pub fn turn_right(&self, steps: u32) -> Self {
+        Self::ALL[(*self as usize + steps as usize) % 8]
+    }
+
+    /// Turn counter-clockwise by the number of 45 degree steps
+    pub fn turn_left(&self, steps: u32) -> Self {
+        Self::ALL[(*self as usize + 8 - steps as usize % 8) % 8]
+    }
+
+    /// Horizontal and vertical step when moving in this direction
+    pub fn delta(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::UpRight => (1, -1),
+            Direction::Right => (1, 0),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (0, 1),
+            Direction::DownLeft => (-1, 1),
+            Direction::Left => (-1, 0),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+
+    // Direction of a step, which must not be (0, 0)
+    fn from_delta(dx: i32, dy: i32) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|d| d.delta() == (dx.signum(), dy.signum()))
+            .unwrap_or(Direction::Right)
+    }
+}
+
+/// A single LED with a position and direction on a display of `XSIZE` by `YSIZE` LEDs.
+///
+/// Sprites always stay on the display, moves beyond the edges stop at the edge.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sprite<const XSIZE: usize, const YSIZE: usize> {
+    x: i32,
+    y: i32,
+    direction: Direction,
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> Sprite<XSIZE, YSIZE> {
+    /// Create a sprite at (x, y) facing right
+    pub fn new(x: i32, y: i32) -> Self {
+        let mut sprite = Self {
+            x: 0,
+            y: 0,
+            direction: Direction::Right,
+        };
+        sprite.set_position(x, y);
+        sprite
+    }
+
+    /// Column of the sprite
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    /// Row of the sprite
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    /// Move the sprite to (x, y)
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.x = x.clamp(0, XSIZE as i32 - 1);
+        self.y = y.clamp(0, YSIZE as i32 - 1);
+    }
+
+    /// Move the sprite horizontally by `dx` columns
+    pub fn change_x(&mut self, dx: i32) {
+        self.set_position(self.x + dx, self.y);
+    }
+
+    /// Move the sprite vertically by `dy` rows
+    pub fn change_y(&mut self, dy: i32) {
+        self.set_position(self.x, self.y + dy);
+    }
+
+    /// Direction the sprite is facing
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Change the direction the sprite is facing
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    /// Turn clockwise by the number of 45 degree steps
+    pub fn turn_right(&mut self, steps: u32) {
+        self.direction = self.direction.turn_right(steps);
+    }
+
+    /// Turn counter-clockwise by the number of 45 degree steps
+    pub fn turn_left(&mut self, steps: u32) {
+        self.direction = self.direction.turn_left(steps);
+    }
+
+    /// Move the sprite `steps` LEDs in the direction it is facing
+    pub fn move_by(&mut self, steps: i32) {
+        let (dx, dy) = self.direction.delta();
+        self.set_position(self.x + dx * steps, self.y + dy * steps);
+    }
+
+    /// Reverse the direction away from the edges the sprite is on, if it is facing them
+    pub fn bounce_on_edge(&mut self) {
+        let (mut dx, mut dy) = self.direction.delta();
+        if (self.x == 0 && dx < 0) || (self.x == XSIZE as i32 - 1 && dx > 0) {
+            dx = -dx;
+        }
+        if (self.y == 0 && dy < 0) || (self.y == YSIZE as i32 - 1 && dy > 0) {
+            dy = -dy;
+        }
+        self.direction = Direction::from_delta(dx, dy);
+    }
+
+    /// Check if the sprite is at the same position as the other sprite
+    pub fn is_touching(&self, other: &Self) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+
+    /// Check if the sprite is on any edge of the display
+    pub fn is_touching_edge(&self) -> bool {
+        self.x == 0 || self.y == 0 || self.x == XSIZE as i32 - 1 || self.y == YSIZE as i32 - 1
+    }
+}
+
+/// How a layer is combined with the layers below it
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Blend {
+    /// Show LEDs set in this layer or any layer below
+    Or,
+    /// Only show LEDs of the layers below that are also set in this layer, i.e. use it as a mask
+    And,
+    /// Invert the LEDs of the layers below that are set in this layer
+    Xor,
+}
+
+/// A frame drawn on top of the layers below it
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Layer<const XSIZE: usize, const YSIZE: usize> {
+    frame: Frame<XSIZE, YSIZE>,
+    blend: Blend,
+    blink: Option<u32>,
+    visible: bool,
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> Layer<XSIZE, YSIZE> {
+    /// Create an empty, visible layer
+    pub const fn new(blend: Blend) -> Self {
+        Self {
+            frame: Frame::empty(),
+            blend,
+            blink: None,
+            visible: true,
+        }
+    }
+
+    /// The frame of the layer
+    pub fn frame(&self) -> &Frame<XSIZE, YSIZE> {
+        &self.frame
+    }
+
+    /// Replace the frame of the layer
+    pub fn set_frame(&mut self, frame: Frame<XSIZE, YSIZE>) {
+        self.frame = frame;
+    }
+
+    /// Clear all LEDs of the layer
+    pub fn clear(&mut self) {
+        self.frame.clear();
+    }
+
+    /// Draw the sprite onto the layer
+    pub fn draw(&mut self, sprite: &Sprite<XSIZE, YSIZE>) {
+        self.frame.set(sprite.x as usize, sprite.y as usize);
+    }
+
+    /// Check if the sprite is on an LED set in this layer, e.g. a wall or the body of a snake
+    pub fn is_touching(&self, sprite: &Sprite<XSIZE, YSIZE>) -> bool {
+        self.frame.is_set(sprite.x as usize, sprite.y as usize)
+    }
+
+    /// How the layer is combined with the layers below it
+    pub fn blend(&self) -> Blend {
+        self.blend
+    }
+
+    /// Change how the layer is combined with the layers below it
+    pub fn set_blend(&mut self, blend: Blend) {
+        self.blend = blend;
+    }
+
+    /// Blink the layer, switching it on and off every `ticks` ticks of the game, or stop blinking
+    /// with `None`
+    pub fn set_blink(&mut self, ticks: Option<u32>) {
+        self.blink = ticks.filter(|t| *t > 0);
+    }
+
+    /// Show or hide the layer
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Check if the layer is shown at the game tick
+    pub fn is_visible(&self, tick: u32) -> bool {
+        self.visible && self.blink.is_none_or(|ticks| (tick / ticks) % 2 == 0)
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> Default for Layer<XSIZE, YSIZE> {
+    fn default() -> Self {
+        Self::new(Blend::Or)
+    }
+}
+
+/// State of a tick-driven game with `LAYERS` layers on a display of `XSIZE` by `YSIZE` LEDs
+pub struct Game<const XSIZE: usize, const YSIZE: usize, const LAYERS: usize> {
+    layers: [Layer<XSIZE, YSIZE>; LAYERS],
+    interval: Duration,
+    tick: u32,
+    score: u32,
+    over: bool,
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const LAYERS: usize> Game<XSIZE, YSIZE, LAYERS> {
+    /// Create a game advancing a tick every `interval`, with empty layers combined using OR
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            layers: [Layer::default(); LAYERS],
+            interval,
+            tick: 0,
+            score: 0,
+            over: false,
+        }
+    }
+
+    /// The layer with the index, layer 0 being at the bottom
+    pub fn layer(&self, index: usize) -> &Layer<XSIZE, YSIZE> {
+        &self.layers[index]
+    }
+
+    /// Modify the layer with the index, layer 0 being at the bottom
+    pub fn layer_mut(&mut self, index: usize) -> &mut Layer<XSIZE, YSIZE> {
+        &mut self.layers[index]
+    }
+
+    /// Time between two ticks
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Change the time between two ticks, e.g. to speed up the game
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Number of ticks since the game started
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Current score
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Add points to the score
+    pub fn add_score(&mut self, points: u32) {
+        self.score = self.score.saturating_add(points);
+    }
+
+    /// Change the score
+    pub fn set_score(&mut self, score: u32) {
+        self.score = score;
+    }
+
+    /// End the game after the current tick
+    pub fn game_over(&mut self) {
+        self.over = true;
+    }
+
+    /// Check if the game has ended
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    /// Restart the game with empty layers and a score of 0, keeping the layer settings
+    pub fn reset(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.clear();
+        }
+        self.tick = 0;
+        self.score = 0;
+        self.over = false;
+    }
+
+    /// Run the update function and advance to the next tick, unless the game is over
+    pub fn step(&mut self, update: impl FnOnce(&mut Self)) {
+        if !self.over {
+            update(self);
+            self.tick = self.tick.wrapping_add(1);
+        }
+    }
+
+    /// Combine the visible layers at the current tick into the frame to display
+    pub fn frame(&self) -> Frame<XSIZE, YSIZE> {
+        let mut frame = Frame::empty();
+        for layer in self.layers.iter().filter(|l| l.is_visible(self.tick)) {
+            match layer.blend {
+                Blend::Or => frame.or(&layer.frame),
+                Blend::And => frame.and(&layer.frame),
+                Blend::Xor => frame.xor(&layer.frame),
+            }
+        }
+        frame
+    }
+}
+
+impl<P, const ROWS: usize, const COLS: usize> LedMatrix<P, ROWS, COLS>
+where
+    P: OutputPin,
+{
+    /// Play the game, calling the update function every tick until the game is over. Like
+    /// MakeCode, "GAME OVER" and the score are then shown on the display.
+    pub async fn play_game<const LAYERS: usize>(
+        &mut self,
+        game: &mut Game<COLS, ROWS, LAYERS>,
+        mut update: impl FnMut(&mut Game<COLS, ROWS, LAYERS>),
+    ) {
+        let mut next = Instant::now();
+        while !game.is_over() {
+            let now = Instant::now();
+            if now >= next {
+                game.step(&mut update);
+                self.apply(game.frame());
+                next += game.interval();
+                // Skip ticks when falling behind rather than running them all at once
+                if next < now {
+                    next = now + game.interval();
+                }
+            }
+            self.render().await;
+        }
+
+        self.scroll("GAME OVER").await;
+        self.show_number(game.score()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type S = Sprite<5, 5>;
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(Direction::from_degrees(0), Direction::Up);
+        assert_eq!(Direction::from_degrees(90), Direction::Right);
+        assert_eq!(Direction::from_degrees(-90), Direction::Left);
+        assert_eq!(Direction::from_degrees(-45), Direction::UpLeft);
+        assert_eq!(Direction::from_degrees(-135), Direction::DownLeft);
+        assert_eq!(Direction::from_degrees(200), Direction::Down);
+        for d in Direction::ALL {
+            assert_eq!(Direction::from_degrees(d.degrees()), d);
+        }
+        assert_eq!(Direction::Left.degrees(), -90);
+        assert_eq!(Direction::Down.degrees(), 180);
+
+        assert_eq!(Direction::Up.turn_right(3), Direction::DownRight);
+        assert_eq!(Direction::Up.turn_left(2), Direction::Left);
+        assert_eq!(Direction::Up.turn_left(10), Direction::Left);
+        assert_eq!(Direction::UpLeft.turn_right(1), Direction::Up);
+    }
+
+    #[test]
+    fn test_sprite_move() {
+        let mut sprite = S::new(2, 2);
+        assert_eq!(sprite.direction(), Direction::Right);
+        sprite.move_by(1);
+        assert_eq!((sprite.x(), sprite.y()), (3, 2));
+
+        // Moves stop at the edge
+        sprite.move_by(5);
+        assert_eq!((sprite.x(), sprite.y()), (4, 2));
+        sprite.set_direction(Direction::UpLeft);
+        sprite.move_by(3);
+        assert_eq!((sprite.x(), sprite.y()), (1, 0));
+
+        sprite.change_y(-1);
+        sprite.change_x(1);
+        assert_eq!((sprite.x(), sprite.y()), (2, 0));
+        assert_eq!(S::new(-3, 7), S::new(0, 4));
+    }
+
+    #[test]
+    fn test_sprite_bounce() {
+        let mut sprite = S::new(4, 0);
+        sprite.set_direction(Direction::UpRight);
+        sprite.bounce_on_edge();
+        assert_eq!(sprite.direction(), Direction::DownLeft);
+
+        let mut sprite = S::new(2, 4);
+        sprite.set_direction(Direction::DownRight);
+        sprite.bounce_on_edge();
+        assert_eq!(sprite.direction(), Direction::UpRight);
+
+        // Sprites facing away from the edge keep their direction
+        sprite.bounce_on_edge();
+        assert_eq!(sprite.direction(), Direction::UpRight);
+        let mut sprite = S::new(2, 2);
+        sprite.set_direction(Direction::Left);
+        sprite.bounce_on_edge();
+        assert_eq!(sprite.direction(), Direction::Left);
+    }
+
+    #[test]
+    fn test_collisions() {
+        let a = S::new(1, 1);
+        let mut b = S::new(2, 1);
+        assert!(!a.is_touching(&b));
+        b.set_direction(Direction::Left);
+        b.move_by(1);
+        assert!(a.is_touching(&b));
+
+        assert!(!a.is_touching_edge());
+        assert!(S::new(4, 2).is_touching_edge());
+        assert!(S::new(2, 0).is_touching_edge());
+
+        let mut walls: Layer<5, 5> = Layer::default();
+        walls.set_frame(Frame::from_image("00000:01000:00000:00000:00000"));
+        assert!(walls.is_touching(&a));
+        assert!(!walls.is_touching(&S::new(2, 1)));
+    }
+
+    #[test]
+    fn test_blend_layers() {
+        let mut game: Game<5, 5, 3> = Game::new(Duration::from_millis(100));
+        game.layer_mut(0)
+            .set_frame(Frame::from_image("11100:00000:00000:00000:00000"));
+        game.layer_mut(1)
+            .set_frame(Frame::from_image("00111:00000:00000:00000:00000"));
+        game.layer_mut(1).set_blend(Blend::Xor);
+        assert_eq!(game.frame(), Frame::from_image("11011:00000:00000:00000:00000"));
+
+        game.layer_mut(2)
+            .set_frame(Frame::from_image("01111:00000:00000:00000:00000"));
+        game.layer_mut(2).set_blend(Blend::And);
+        assert_eq!(game.frame(), Frame::from_image("01011:00000:00000:00000:00000"));
+
+        game.layer_mut(2).set_visible(false);
+        assert_eq!(game.frame(), Frame::from_image("11011:00000:00000:00000:00000"));
+    }
+
+    #[test]
+    fn test_blink() {
+        let mut game: Game<5, 5, 2> = Game::new(Duration::from_millis(100));
+        game.layer_mut(0).draw(&S::new(0, 0));
+        game.layer_mut(1).draw(&S::new(4, 4));
+        game.layer_mut(1).set_blink(Some(2));
+
+        let mut shown = [false; 6];
+        for s in shown.iter_mut() {
+            let frame = game.frame();
+            assert!(frame.is_set(0, 0));
+            *s = frame.is_set(4, 4);
+            game.step(|_| {});
+        }
+        assert_eq!(shown, [true, true, false, false, true, true]);
+
+        game.layer_mut(1).set_blink(None);
+        assert!(game.frame().is_set(4, 4));
+    }
+
+    #[test]
+    fn test_game_loop() {
+        let mut game: Game<5, 5, 1> = Game::new(Duration::from_millis(100));
+        let mut ball = S::new(0, 2);
+        for _ in 0..4 {
+            game.step(|game| {
+                ball.move_by(1);
+                game.add_score(1);
+                if ball.is_touching_edge() && ball.x() > 0 {
+                    game.game_over();
+                }
+                let layer = game.layer_mut(0);
+                layer.clear();
+                layer.draw(&ball);
+            });
+        }
+        assert_eq!(game.tick(), 4);
+        assert!(game.is_over());
+        assert_eq!(game.score(), 4);
+        assert_eq!(game.frame(), Frame::from_image("00000:00000:00001:00000:00000"));
+
+        // No more ticks once the game is over
+        game.step(|game| game.add_score(1));
+        assert_eq!(game.tick(), 4);
+
+        game.reset();
+        assert!(!game.is_over());
+        assert_eq!((game.tick(), game.score()), (0, 0));
+        assert_eq!(game.frame(), Frame::empty());
+    }
+}
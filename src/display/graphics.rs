@@ -0,0 +1,220 @@
+//! Support for drawing onto frames and LED matrices using `embedded-graphics`
+use core::convert::Infallible;
+
+use embedded_graphics_core::geometry::{OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::raw::{RawData, RawU4};
+use embedded_graphics_core::pixelcolor::{BinaryColor, PixelColor};
+use embedded_graphics_core::prelude::{DrawTarget, Pixel};
+use embedded_hal::digital::OutputPin;
+
+use super::{Frame, GreyscaleFrame, LedMatrix};
+
+/// Greyscale color with the levels of a [`GreyscaleFrame`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GreyscaleColor(u8);
+
+impl GreyscaleColor {
+    /// LED turned off
+    pub const OFF: GreyscaleColor = GreyscaleColor(0);
+
+    /// LED at full brightness
+    pub const ON: GreyscaleColor = GreyscaleColor(GreyscaleFrame::<0, 0>::MAX_LEVEL);
+
+    /// Create a new color with a custom level
+    pub const fn new(level: u8) -> Self {
+        if level > Self::ON.0 {
+            Self::ON
+        } else {
+            Self(level)
+        }
+    }
+
+    /// Return the level value
+    pub const fn level(&self) -> u8 {
+        self.0
+    }
+}
+
+impl PixelColor for GreyscaleColor {
+    type Raw = RawU4;
+}
+
+impl From<RawU4> for GreyscaleColor {
+    fn from(raw: RawU4) -> Self {
+        Self::new(raw.into_inner())
+    }
+}
+
+impl From<GreyscaleColor> for RawU4 {
+    fn from(color: GreyscaleColor) -> Self {
+        RawU4::new(color.0)
+    }
+}
+
+impl From<BinaryColor> for GreyscaleColor {
+    fn from(color: BinaryColor) -> Self {
+        match color {
+            BinaryColor::Off => Self::OFF,
+            BinaryColor::On => Self::ON,
+        }
+    }
+}
+
+// Pixel coordinates if they are within the frame
+fn position<const XSIZE: usize, const YSIZE: usize, C: PixelColor>(pixel: &Pixel<C>) -> Option<(usize, usize)> {
+    let Pixel(point, _) = pixel;
+    let (x, y) = (usize::try_from(point.x).ok()?, usize::try_from(point.y).ok()?);
+    (x < XSIZE && y < YSIZE).then_some((x, y))
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> OriginDimensions for Frame<XSIZE, YSIZE, WORDS> {
+    fn size(&self) -> Size {
+        Size::new(XSIZE as u32, YSIZE as u32)
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> DrawTarget for Frame<XSIZE, YSIZE, WORDS> {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            if let Some((x, y)) = position::<XSIZE, YSIZE, _>(&pixel) {
+                match pixel.1 {
+                    BinaryColor::On => self.set(x, y),
+                    BinaryColor::Off => self.unset(x, y),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> OriginDimensions for GreyscaleFrame<XSIZE, YSIZE> {
+    fn size(&self) -> Size {
+        Size::new(XSIZE as u32, YSIZE as u32)
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> DrawTarget for GreyscaleFrame<XSIZE, YSIZE> {
+    type Color = GreyscaleColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            if let Some((x, y)) = position::<XSIZE, YSIZE, _>(&pixel) {
+                self.set(x, y, pixel.1.level());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P, const ROWS: usize, const COLS: usize> OriginDimensions for LedMatrix<P, ROWS, COLS>
+where
+    P: OutputPin,
+{
+    fn size(&self) -> Size {
+        Size::new(COLS as u32, ROWS as u32)
+    }
+}
+
+impl<P, const ROWS: usize, const COLS: usize> DrawTarget for LedMatrix<P, ROWS, COLS>
+where
+    P: OutputPin,
+{
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            if let Some((x, y)) = position::<COLS, ROWS, _>(&pixel) {
+                match pixel.1 {
+                    BinaryColor::On => self.on(x, y),
+                    BinaryColor::Off => self.off(x, y),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+
+    use super::*;
+    use crate::display::mock::pins;
+
+    #[test]
+    fn test_draw_frame() {
+        let mut frame: Frame<5, 5> = Frame::empty();
+        Rectangle::new(Point::new(0, 0), Size::new(5, 4))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(&mut frame)
+            .unwrap();
+        let mut expected: Frame<5, 5> = Frame::empty();
+        expected.rect(0, 0, 4, 3);
+        assert_eq!(frame, expected);
+
+        // Drawing outside of the frame is clipped
+        let mut frame: Frame<5, 5> = Frame::empty();
+        Line::new(Point::new(-3, -3), Point::new(7, 7))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(&mut frame)
+            .unwrap();
+        let mut expected: Frame<5, 5> = Frame::empty();
+        expected.line(0, 0, 4, 4);
+        assert_eq!(frame, expected);
+
+        DrawTarget::clear(&mut frame, BinaryColor::On).unwrap();
+        Rectangle::new(Point::new(1, 1), Size::new(3, 3))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(&mut frame)
+            .unwrap();
+        let mut expected: Frame<5, 5> = Frame::empty();
+        expected.rect(0, 0, 4, 4);
+        assert_eq!(frame, expected);
+    }
+
+    #[test]
+    fn test_draw_greyscale_frame() {
+        let mut frame: GreyscaleFrame<5, 5> = GreyscaleFrame::empty();
+        for level in 0..5 {
+            Line::new(Point::new(level, 0), Point::new(level, 4))
+                .into_styled(PrimitiveStyle::with_stroke(GreyscaleColor::new(2 * level as u8), 1))
+                .draw(&mut frame)
+                .unwrap();
+        }
+        let row = [0, 2, 4, 6, 8];
+        assert_eq!(frame, GreyscaleFrame::new([row; 5]));
+
+        assert_eq!(GreyscaleColor::new(12), GreyscaleColor::ON);
+        assert_eq!(GreyscaleColor::from(BinaryColor::On), GreyscaleColor::ON);
+    }
+
+    #[test]
+    fn test_draw_matrix() {
+        let mut matrix: LedMatrix<_, 5, 5> = LedMatrix::new(pins(), pins());
+        assert_eq!(matrix.size(), Size::new(5, 5));
+
+        Line::new(Point::new(0, 2), Point::new(4, 2))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(&mut matrix)
+            .unwrap();
+        let mut expected: Frame<5, 5> = Frame::empty();
+        expected.line(0, 2, 4, 2);
+        assert_eq!(Frame::from(matrix.back_buffer), expected);
+    }
+}
@@ -0,0 +1,190 @@
+use super::types::Frame;
+
+/// An NxM frame with a brightness level per LED, in the range `0..=MAX_LEVEL`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct GreyscaleFrame<const XSIZE: usize, const YSIZE: usize> {
+    levels: [[u8; XSIZE]; YSIZE],
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> core::fmt::Debug for GreyscaleFrame<XSIZE, YSIZE> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for row in self.levels.iter() {
+            for level in row.iter() {
+                write!(f, "{}", level)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const XSIZE: usize, const YSIZE: usize> defmt::Format for GreyscaleFrame<XSIZE, YSIZE> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        for row in self.levels.iter() {
+            defmt::write!(f, "{=[u8]}\n", &row[..]);
+        }
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> GreyscaleFrame<XSIZE, YSIZE> {
+    /// Highest brightness level of a single LED
+    pub const MAX_LEVEL: u8 = 9;
+
+    /// Create an empty frame
+    pub const fn empty() -> Self {
+        Self {
+            levels: [[0; XSIZE]; YSIZE],
+        }
+    }
+
+    /// Create a frame from rows of levels. Levels above `MAX_LEVEL` are clamped.
+    pub const fn new(mut levels: [[u8; XSIZE]; YSIZE]) -> Self {
+        let mut y = 0;
+        while y < YSIZE {
+            let mut x = 0;
+            while x < XSIZE {
+                if levels[y][x] > Self::MAX_LEVEL {
+                    levels[y][x] = Self::MAX_LEVEL;
+                }
+                x += 1;
+            }
+            y += 1;
+        }
+        Self { levels }
+    }
+
+    /// Create a frame with all LEDs set in `frame` at the provided level
+    pub fn from_frame<const WORDS: usize>(frame: &Frame<XSIZE, YSIZE, WORDS>, level: u8) -> Self {
+        let level = level.min(Self::MAX_LEVEL);
+        let mut result = Self::empty();
+        for y in 0..YSIZE {
+            for x in 0..XSIZE {
+                if frame.is_set(x, y) {
+                    result.levels[y][x] = level;
+                }
+            }
+        }
+        result
+    }
+
+    /// Clear this frame (empty)
+    pub fn clear(&mut self) {
+        self.fill(0);
+    }
+
+    /// Set all LEDs to the provided level
+    pub fn fill(&mut self, level: u8) {
+        let level = level.min(Self::MAX_LEVEL);
+        for row in self.levels.iter_mut() {
+            row.fill(level);
+        }
+    }
+
+    /// Set the level of (x, y) on this frame
+    pub fn set(&mut self, x: usize, y: usize, level: u8) {
+        self.levels[y][x] = level.min(Self::MAX_LEVEL);
+    }
+
+    /// Return the level of (x, y) on this frame
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.levels[y][x]
+    }
+
+    /// Check if (x, y) is lit on this frame
+    pub fn is_set(&self, x: usize, y: usize) -> bool {
+        self.levels[y][x] > 0
+    }
+
+    /// Scale all levels by `level / MAX_LEVEL`, e.g. to fade the frame in or out
+    pub fn scale(&mut self, level: u8) {
+        let level = level.min(Self::MAX_LEVEL);
+        for row in self.levels.iter_mut() {
+            for l in row.iter_mut() {
+                *l = (*l as u16 * level as u16).div_ceil(Self::MAX_LEVEL as u16) as u8;
+            }
+        }
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> Default for GreyscaleFrame<XSIZE, YSIZE> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> From<Frame<XSIZE, YSIZE, WORDS>>
+    for GreyscaleFrame<XSIZE, YSIZE>
+{
+    fn from(frame: Frame<XSIZE, YSIZE, WORDS>) -> Self {
+        Self::from_frame(&frame, Self::MAX_LEVEL)
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> From<GreyscaleFrame<XSIZE, YSIZE>>
+    for Frame<XSIZE, YSIZE, WORDS>
+{
+    /// Convert to a frame with all LEDs that are not fully off set
+    fn from(frame: GreyscaleFrame<XSIZE, YSIZE>) -> Self {
+        let mut result = Frame::empty();
+        for y in 0..YSIZE {
+            for x in 0..XSIZE {
+                if frame.is_set(x, y) {
+                    result.set(x, y);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::fonts::CHECK_MARK;
+
+    #[test]
+    fn test_greyscale_frame() {
+        let mut frame: GreyscaleFrame<5, 5> = GreyscaleFrame::empty();
+        frame.set(0, 0, 3);
+        frame.set(4, 4, 20);
+        assert_eq!(frame.get(0, 0), 3);
+        assert_eq!(frame.get(4, 4), GreyscaleFrame::<5, 5>::MAX_LEVEL);
+        assert_eq!(frame.get(1, 0), 0);
+        assert!(frame.is_set(0, 0));
+        assert!(!frame.is_set(1, 0));
+
+        let frame: GreyscaleFrame<3, 2> = GreyscaleFrame::new([[0, 5, 12], [9, 1, 0]]);
+        assert_eq!(frame.get(2, 0), 9);
+        assert_eq!(frame.get(1, 1), 1);
+    }
+
+    #[test]
+    fn test_scale() {
+        let mut frame: GreyscaleFrame<3, 1> = GreyscaleFrame::new([[9, 4, 1]]);
+        frame.scale(9);
+        assert_eq!(frame, GreyscaleFrame::new([[9, 4, 1]]));
+
+        frame.scale(3);
+        assert_eq!(frame, GreyscaleFrame::new([[3, 2, 1]]));
+
+        frame.scale(0);
+        assert_eq!(frame, GreyscaleFrame::empty());
+    }
+
+    #[test]
+    fn test_frame_conversion() {
+        let grey: GreyscaleFrame<5, 5> = CHECK_MARK.into();
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = if CHECK_MARK.is_set(x, y) { 9 } else { 0 };
+                assert_eq!(grey.get(x, y), expected);
+            }
+        }
+        assert_eq!(Frame::from(grey), CHECK_MARK);
+
+        let grey = GreyscaleFrame::from_frame(&CHECK_MARK, 2);
+        assert_eq!(grey.get(4, 1), 2);
+        assert_eq!(Frame::from(grey), CHECK_MARK);
+    }
+}
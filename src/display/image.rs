@@ -0,0 +1,309 @@
+//! Conversion between frames and MicroPython image strings
+//!
+//! Images are written as rows of brightness digits `0`-`9` separated by `:` or newlines, e.g.
+//! `"09090:99999:99999:09990:00900"` for a heart. A trailing separator is accepted, as written by
+//! MicroPython. Parsing is available in `const` contexts, so images can be written inline:
+//!
+//! ```
+//! use microbit_bsp::display::Frame;
+//!
+//! const HEART: Frame<5, 5> = Frame::from_image("09090:99999:99999:09990:00900");
+//! ```
+use core::fmt;
+use core::str::FromStr;
+
+use super::{Frame, GreyscaleFrame};
+
+/// Errors produced when parsing image strings
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ParseError {
+    /// A character other than a digit, `:` or a newline
+    InvalidCharacter {
+        /// Row of the character
+        row: usize,
+        /// Column of the character
+        column: usize,
+    },
+    /// A row does not match the width of the frame
+    RowLength {
+        /// Row with the wrong length
+        row: usize,
+        /// Width of the frame
+        expected: usize,
+    },
+    /// The number of rows does not match the height of the frame
+    RowCount {
+        /// Height of the frame
+        expected: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidCharacter { row, column } => {
+                write!(f, "invalid character at row {}, column {}", row, column)
+            }
+            ParseError::RowLength { row, expected } => write!(f, "row {} does not have {} columns", row, expected),
+            ParseError::RowCount { expected } => write!(f, "image does not have {} rows", expected),
+        }
+    }
+}
+
+// Parse the levels of all LEDs in an image string
+const fn parse_levels<const XSIZE: usize, const YSIZE: usize>(image: &str) -> Result<[[u8; XSIZE]; YSIZE], ParseError> {
+    let bytes = image.as_bytes();
+    let mut levels = [[0; XSIZE]; YSIZE];
+    let (mut x, mut y) = (0, 0);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b':' | b'\n' => {
+                if x != XSIZE {
+                    return Err(ParseError::RowLength {
+                        row: y,
+                        expected: XSIZE,
+                    });
+                }
+                x = 0;
+                y += 1;
+            }
+            b @ b'0'..=b'9' => {
+                if y >= YSIZE {
+                    return Err(ParseError::RowCount { expected: YSIZE });
+                }
+                if x >= XSIZE {
+                    return Err(ParseError::RowLength {
+                        row: y,
+                        expected: XSIZE,
+                    });
+                }
+                levels[y][x] = b - b'0';
+                x += 1;
+            }
+            _ => return Err(ParseError::InvalidCharacter { row: y, column: x }),
+        }
+        i += 1;
+    }
+
+    // The last row may or may not be followed by a separator
+    if (x == 0 && y == YSIZE) || (x == XSIZE && y + 1 == YSIZE) {
+        Ok(levels)
+    } else if x != 0 && x != XSIZE {
+        Err(ParseError::RowLength {
+            row: y,
+            expected: XSIZE,
+        })
+    } else {
+        Err(ParseError::RowCount { expected: YSIZE })
+    }
+}
+
+// Panic with a message describing the error, for use in const contexts
+const fn invalid_image(error: ParseError) -> ! {
+    match error {
+        ParseError::InvalidCharacter { .. } => panic!("image contains an invalid character"),
+        ParseError::RowLength { .. } => panic!("image row length does not match the frame width"),
+        ParseError::RowCount { .. } => panic!("image row count does not match the frame height"),
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> Frame<XSIZE, YSIZE, WORDS> {
+    /// Parse an image string. All LEDs with a level above 0 are set.
+    pub const fn parse(image: &str) -> Result<Self, ParseError> {
+        let levels = match parse_levels::<XSIZE, YSIZE>(image) {
+            Ok(levels) => levels,
+            Err(e) => return Err(e),
+        };
+        let mut frame = Self::empty();
+        let mut y = 0;
+        while y < YSIZE {
+            let mut x = 0;
+            while x < XSIZE {
+                if levels[y][x] > 0 {
+                    frame.set(x, y);
+                }
+                x += 1;
+            }
+            y += 1;
+        }
+        Ok(frame)
+    }
+
+    /// Create a frame from an image string, panicking if it is invalid. When used to initialize
+    /// a constant, an invalid image fails to compile.
+    pub const fn from_image(image: &str) -> Self {
+        match Self::parse(image) {
+            Ok(frame) => frame,
+            Err(e) => invalid_image(e),
+        }
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> GreyscaleFrame<XSIZE, YSIZE> {
+    /// Parse an image string with the level of each LED
+    pub const fn parse(image: &str) -> Result<Self, ParseError> {
+        match parse_levels::<XSIZE, YSIZE>(image) {
+            Ok(levels) => Ok(Self::new(levels)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Create a frame from an image string, panicking if it is invalid. When used to initialize
+    /// a constant, an invalid image fails to compile.
+    pub const fn from_image(image: &str) -> Self {
+        match Self::parse(image) {
+            Ok(frame) => frame,
+            Err(e) => invalid_image(e),
+        }
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> FromStr for Frame<XSIZE, YSIZE, WORDS> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> FromStr for GreyscaleFrame<XSIZE, YSIZE> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// Write levels as an image string, with rows separated by `:`
+fn write_image(
+    f: &mut fmt::Formatter<'_>,
+    xsize: usize,
+    ysize: usize,
+    level: impl Fn(usize, usize) -> u8,
+) -> fmt::Result {
+    for y in 0..ysize {
+        if y > 0 {
+            write!(f, ":")?;
+        }
+        for x in 0..xsize {
+            write!(f, "{}", level(x, y))?;
+        }
+    }
+    Ok(())
+}
+
+/// Format as an image string, with set LEDs at the highest level
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> fmt::Display for Frame<XSIZE, YSIZE, WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_image(f, XSIZE, YSIZE, |x, y| {
+            if self.is_set(x, y) {
+                GreyscaleFrame::<XSIZE, YSIZE>::MAX_LEVEL
+            } else {
+                0
+            }
+        })
+    }
+}
+
+/// Format as an image string
+impl<const XSIZE: usize, const YSIZE: usize> fmt::Display for GreyscaleFrame<XSIZE, YSIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_image(f, XSIZE, YSIZE, |x, y| self.get(x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::*;
+    use crate::display::fonts::{ALL_CLOCKS, HEART};
+
+    const INLINE: Frame<5, 5> = Frame::from_image("09090:99999:99999:09990:00900");
+
+    #[test]
+    fn test_parse_frame() {
+        assert_eq!(INLINE, HEART);
+        assert_eq!(Frame::parse("09090:99999:99999:09990:00900:"), Ok(HEART));
+        assert_eq!("09090\n99999\n99999\n09990\n00900\n".parse(), Ok(HEART));
+
+        // Any level above 0 is set
+        assert_eq!("01010:12345:99999:05550:00200".parse(), Ok(HEART));
+
+        let wide: Frame<7, 2> = Frame::from_image("1000001:0111110");
+        assert!(wide.is_set(6, 0));
+        assert!(!wide.is_set(6, 1));
+    }
+
+    #[test]
+    fn test_parse_greyscale() {
+        let frame: GreyscaleFrame<3, 2> = GreyscaleFrame::from_image("019:540");
+        assert_eq!(frame, GreyscaleFrame::new([[0, 1, 9], [5, 4, 0]]));
+        assert_eq!("019:540:".parse(), Ok(frame));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        type F = Frame<5, 5>;
+        assert_eq!(
+            F::parse("09090:99999:99x99:09990:00900"),
+            Err(ParseError::InvalidCharacter { row: 2, column: 2 })
+        );
+        assert_eq!(
+            F::parse("09090:9999:99999:09990:00900"),
+            Err(ParseError::RowLength { row: 1, expected: 5 })
+        );
+        assert_eq!(
+            F::parse("09090:999999:99999:09990:00900"),
+            Err(ParseError::RowLength { row: 1, expected: 5 })
+        );
+        assert_eq!(
+            F::parse("09090:99999:99999:09990:009"),
+            Err(ParseError::RowLength { row: 4, expected: 5 })
+        );
+        assert_eq!(
+            F::parse("09090:99999:99999:09990"),
+            Err(ParseError::RowCount { expected: 5 })
+        );
+        assert_eq!(
+            F::parse("09090:99999:99999:09990:00900:00000"),
+            Err(ParseError::RowCount { expected: 5 })
+        );
+        assert_eq!(F::parse(""), Err(ParseError::RowCount { expected: 5 }));
+        assert_eq!(
+            GreyscaleFrame::<5, 5>::parse("0909:"),
+            Err(ParseError::RowLength { row: 0, expected: 5 })
+        );
+
+        let mut message: heapless::String<64> = heapless::String::new();
+        write!(message, "{}", ParseError::InvalidCharacter { row: 2, column: 2 }).unwrap();
+        assert_eq!(message, "invalid character at row 2, column 2");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_image() {
+        let _: Frame<5, 5> = Frame::from_image("09090");
+    }
+
+    #[test]
+    fn test_format() {
+        let mut image: heapless::String<64> = heapless::String::new();
+        write!(image, "{}", HEART).unwrap();
+        assert_eq!(image, "09090:99999:99999:09990:00900");
+
+        for clock in ALL_CLOCKS {
+            image.clear();
+            write!(image, "{}", clock).unwrap();
+            assert_eq!(image.parse(), Ok(clock));
+        }
+
+        let frame: GreyscaleFrame<3, 2> = GreyscaleFrame::new([[0, 1, 9], [5, 4, 0]]);
+        image.clear();
+        write!(image, "{}", frame).unwrap();
+        assert_eq!(image, "019:540");
+        assert_eq!(image.parse(), Ok(frame));
+    }
+}
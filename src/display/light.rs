@@ -0,0 +1,189 @@
+//! Ambient light sensing using the LED matrix
+//!
+//! The LEDs are reverse biased, charging their junction capacitance, after which the column pins
+//! are released and the time until they discharge is measured. The photocurrent grows with the
+//! ambient light, so the LEDs discharge faster in brighter light. The falling edges of the
+//! columns are awaited, so other tasks keep running during the measurement.
+use embassy_futures::join::join_array;
+use embassy_time::{with_deadline, Duration, Instant, Timer};
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::digital::Wait;
+
+use super::LedMatrix;
+
+/// Time the LEDs are reverse biased before measuring
+pub const LIGHT_SENSE_CHARGE: Duration = Duration::from_micros(100);
+
+/// Discharge time in the brightest light, and shorter times are reported as the maximum level
+pub const LIGHT_SENSE_MIN: Duration = Duration::from_micros(100);
+
+/// Longest time to wait for the LEDs to discharge, reported as complete darkness
+pub const LIGHT_SENSE_TIMEOUT: Duration = Duration::from_millis(4);
+
+/// A matrix pin that can also be read and awaited as an input, needed to sense light with the
+/// LEDs
+pub trait SensePin: OutputPin + InputPin + Wait {
+    /// Stop driving the pin and read it as a floating input
+    fn set_as_input(&mut self);
+
+    /// Drive the pin again at the last level set
+    fn set_as_output(&mut self);
+}
+
+/// Convert the discharge time of the LEDs to a light level between 0 (dark) and 255 (bright).
+///
+/// The photocurrent, and so the light intensity, is inversely proportional to the discharge time.
+pub fn light_level(discharge: Duration) -> u8 {
+    let min = LIGHT_SENSE_MIN.as_ticks().max(1);
+    let max = LIGHT_SENSE_TIMEOUT.as_ticks();
+    let t = discharge.as_ticks().clamp(min, max);
+    (255 * min * (max - t) / (t * (max - min))) as u8
+}
+
+impl<P, const ROWS: usize, const COLS: usize> LedMatrix<P, ROWS, COLS>
+where
+    P: SensePin,
+{
+    /// Measure the ambient light level, like `display.read_light_level()` in MicroPython. Returns
+    /// a value from 0 (dark) to 255 (bright).
+    ///
+    /// The display is dark during the measurement, which takes up to [`LIGHT_SENSE_TIMEOUT`].
+    /// Call between two [`render`](Self::render) calls to measure while the display is refreshed.
+    pub async fn read_light_level(&mut self) -> u8 {
+        self.charge();
+        Timer::after(LIGHT_SENSE_CHARGE).await;
+        self.measure_discharge().await
+    }
+
+    /// Release the LEDs charged for [`LIGHT_SENSE_CHARGE`] and await their discharge, returning
+    /// the light level. Columns still charged after [`LIGHT_SENSE_TIMEOUT`] count as dark.
+    ///
+    /// The discharge of a column is timed when the task is woken up by its falling edge, so a
+    /// busy executor makes the level darker than it is.
+    pub(crate) async fn measure_discharge(&mut self) -> u8 {
+        self.release();
+        let released = Released(self);
+        let start = Instant::now();
+        let deadline = start + LIGHT_SENSE_TIMEOUT;
+        let discharge = join_array(released.0.pin_cols.each_mut().map(|col| async move {
+            match with_deadline(deadline, col.wait_for_low()).await {
+                Ok(Ok(())) => (Instant::now() - start).min(LIGHT_SENSE_TIMEOUT),
+                _ => LIGHT_SENSE_TIMEOUT,
+            }
+        }))
+        .await;
+        drop(released);
+
+        let total: u64 = discharge.iter().map(|d| d.as_ticks()).sum();
+        light_level(Duration::from_ticks(total / COLS.max(1) as u64))
+    }
+
+    /// Reverse bias all LEDs by driving the rows low and the columns high
+    pub(crate) fn charge(&mut self) {
+        for row in self.pin_rows.iter_mut() {
+            row.set_low().ok();
+        }
+        for col in self.pin_cols.iter_mut() {
+            col.set_high().ok();
+        }
+    }
+
+    /// Release the columns so the LEDs discharge
+    fn release(&mut self) {
+        for col in self.pin_cols.iter_mut() {
+            col.set_as_input();
+        }
+    }
+
+    /// Drive the columns again with all LEDs off
+    fn restore(&mut self) {
+        for col in self.pin_cols.iter_mut() {
+            col.set_high().ok();
+            col.set_as_output();
+        }
+        if !self.enabled {
+            self.power_down();
+        }
+    }
+}
+
+// Drives the columns again when dropped, even if the measurement is cancelled
+struct Released<'m, P: SensePin + 'static, const ROWS: usize, const COLS: usize>(&'m mut LedMatrix<P, ROWS, COLS>);
+
+impl<P: SensePin, const ROWS: usize, const COLS: usize> Drop for Released<'_, P, ROWS, COLS> {
+    fn drop(&mut self) {
+        self.0.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use super::*;
+    use crate::display::mock::pins;
+
+    #[test]
+    fn test_light_level() {
+        assert_eq!(light_level(Duration::from_ticks(0)), 255);
+        assert_eq!(light_level(LIGHT_SENSE_MIN), 255);
+        assert_eq!(light_level(LIGHT_SENSE_TIMEOUT), 0);
+        assert_eq!(light_level(LIGHT_SENSE_TIMEOUT * 2), 0);
+
+        // Levels decrease with the discharge time
+        let mut last = 255;
+        for ms in 1..4 {
+            let level = light_level(Duration::from_millis(ms));
+            assert!(level < last);
+            last = level;
+        }
+        assert!(last > 0);
+    }
+
+    #[test]
+    fn test_sense_pins() {
+        let rows = pins::<2>();
+        let cols = pins::<3>();
+        let mut matrix: LedMatrix<_, 2, 3> = LedMatrix::new(rows.clone(), cols.clone());
+        matrix.on(0, 0);
+        matrix.select_row();
+        matrix.light_row();
+
+        matrix.charge();
+        assert!(rows.iter().all(|r| !r.is_high()));
+        assert!(cols.iter().all(|c| c.is_high()));
+
+        matrix.release();
+        assert!(cols.iter().all(|c| c.is_input()));
+        assert!(rows.iter().all(|r| !r.is_input()));
+
+        // The display continues from the frame buffer
+        matrix.restore();
+        assert!(cols.iter().all(|c| !c.is_input() && c.is_high()));
+        matrix.select_row();
+        matrix.light_row();
+        assert!(rows[0].is_high());
+        assert!(!cols[0].is_high());
+    }
+
+    #[test]
+    fn test_measure_discharge() {
+        let cols = pins::<3>();
+        let mut matrix: LedMatrix<_, 2, 3> = LedMatrix::new(pins::<2>(), cols.clone());
+
+        // Waits for the columns still charged instead of polling them, and drives them again
+        // when cancelled
+        matrix.charge();
+        cols[0].clone().set_low().unwrap();
+        assert!(matrix.measure_discharge().now_or_never().is_none());
+        assert!(cols.iter().all(|c| !c.is_input() && c.is_high()));
+
+        // Columns discharged right away are the brightest light
+        matrix.charge();
+        for col in cols.iter() {
+            col.clone().set_low().unwrap();
+        }
+        assert_eq!(matrix.measure_discharge().now_or_never(), Some(255));
+        assert!(cols.iter().all(|c| !c.is_input() && c.is_high()));
+    }
+}
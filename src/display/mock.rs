@@ -0,0 +1,98 @@
+//! Mock pins for testing the display drivers on the host
+extern crate std;
+
+use core::cell::Cell;
+use core::convert::Infallible;
+use std::rc::Rc;
+
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+use embedded_hal_async::digital::Wait;
+
+use super::SensePin;
+
+/// Pin recording its last driven level and whether it is an input
+#[derive(Clone, Default)]
+pub(crate) struct MockPin {
+    level: Rc<Cell<bool>>,
+    input: Rc<Cell<bool>>,
+}
+
+impl MockPin {
+    pub(crate) fn is_high(&self) -> bool {
+        self.level.get()
+    }
+
+    pub(crate) fn is_input(&self) -> bool {
+        self.input.get()
+    }
+}
+
+impl ErrorType for MockPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for MockPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.level.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.level.set(true);
+        Ok(())
+    }
+}
+
+impl InputPin for MockPin {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(self.level.get())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(!self.level.get())
+    }
+}
+
+// Levels only change when set, so waiting for a level that is not there never completes
+impl Wait for MockPin {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        if !self.level.get() {
+            core::future::pending::<()>().await;
+        }
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        if self.level.get() {
+            core::future::pending::<()>().await;
+        }
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+}
+
+impl SensePin for MockPin {
+    fn set_as_input(&mut self) {
+        self.input.set(true);
+    }
+
+    fn set_as_output(&mut self) {
+        self.input.set(false);
+    }
+}
+
+/// Create N independent mock pins
+pub(crate) fn pins<const N: usize>() -> [MockPin; N] {
+    core::array::from_fn(|_| MockPin::default())
+}
@@ -1,29 +1,118 @@
 //! Driver a NxM LED matrix display
 //!
-//! * Can display 5x5 bitmaps from raw data or characters
+//! * Can display 5x5 bitmaps from raw data or characters, including Latin-1 letters, symbols, arrows and icons
+//! * The standard MicroPython images, such as [`fonts::HEART`] and [`fonts::ALL_CLOCKS`]
+//! * Per-LED greyscale levels using [`GreyscaleFrame`]
+//! * Drawing lines, rectangles and circles onto frames
+//! * Parsing and formatting frames as MicroPython image strings
+//! * A binary format for frame sequences with per-frame durations
 //! * Methods for scrolling text across LED matrix or displaying a bitmap for a duration
-use embassy_time::{block_for, Duration, Instant, Timer};
+//! * Showing numbers and plotting values as bar graphs
+//! * Double buffered updates, swapped in between two scans of the display
+//! * Sensing the ambient light level with the LEDs
+//! * Sprites, layers and a game loop for simple games in the [`game`] module
+use core::fmt::{self, Write};
+
+use embassy_time::{Duration, Instant, Timer, TICK_HZ};
 use embedded_hal::digital::OutputPin;
 
+use self::fonts::Font;
+
 pub mod fonts;
+pub mod game;
 
 mod types;
 pub use types::*;
 
-const REFRESH_INTERVAL: Duration = Duration::from_micros(500);
+mod greyscale;
+pub use greyscale::*;
+
+mod draw;
+
+mod image;
+pub use image::*;
+
+mod value;
+pub use value::*;
+
+mod sequence;
+pub use sequence::*;
+
+mod animation;
+pub use animation::*;
+
+#[cfg(feature = "embedded-graphics")]
+mod graphics;
+#[cfg(feature = "embedded-graphics")]
+pub use graphics::*;
+
+mod refresh;
+pub use refresh::*;
+
+mod light;
+pub use light::*;
+
+#[cfg(test)]
+mod mock;
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Default text scrolling speed in columns per second
+pub const DEFAULT_SCROLL_SPEED: u32 = 10;
+
+/// Default number of empty columns between scrolled characters
+pub const DEFAULT_SCROLL_GAP: usize = 1;
+
+/// Duration a number consisting of a single character is shown by [`LedMatrix::show_number`]
+pub const SHOW_NUMBER_DURATION: Duration = Duration::from_millis(1000);
+
+// Share of the refresh interval (out of 64) each greyscale level is lit
+const LEVEL_DUTY: [u64; 10] = [0, 1, 2, 4, 6, 10, 16, 26, 40, 64];
+
+// Ticks a row is refreshed with a timer running at `tick_hz`. With coarse ticks, such as the
+// 32768 Hz RTC, the row is refreshed longer so each step of `LEVEL_DUTY` is at least one tick.
+const fn row_ticks(tick_hz: u64) -> u64 {
+    let ticks = REFRESH_INTERVAL.as_micros() * tick_hz / 1_000_000;
+    let min = LEVEL_DUTY[LEVEL_DUTY.len() - 1];
+    if ticks < min {
+        min
+    } else {
+        ticks
+    }
+}
+
+// Ticks each greyscale level is lit during a row refresh with a timer running at `tick_hz`.
+// Levels that round to the same number of ticks are lit one tick longer than the level below,
+// so every non-zero level is visible and brighter than the previous one.
+fn level_ticks(brightness: Brightness, tick_hz: u64) -> [u64; LEVEL_DUTY.len()] {
+    let period = row_ticks(tick_hz);
+    let scale = LEVEL_DUTY[LEVEL_DUTY.len() - 1] * (u64::from(Brightness::MAX.level()) + 1);
+    let mut ticks = [0; LEVEL_DUTY.len()];
+    for level in 1..LEVEL_DUTY.len() {
+        let duty = LEVEL_DUTY[level] * (u64::from(brightness.level()) + 1);
+        ticks[level] = (period * duty / scale).max(ticks[level - 1] + 1).min(period);
+    }
+    ticks
+}
 
 /// Led matrix driver supporting arbitrary sized led matrixes.
 ///
-/// NOTE: Currently restricted by 8 bits width
+/// The matrix is double buffered: all changes are made to a back buffer, which replaces the
+/// displayed frame buffer when a full scan of the rows has completed. This avoids showing half
+/// of the old and half of the new frame.
 pub struct LedMatrix<P, const ROWS: usize, const COLS: usize>
 where
     P: OutputPin + 'static,
 {
     pin_rows: [P; ROWS],
     pin_cols: [P; COLS],
-    frame_buffer: Frame<COLS, ROWS>,
+    frame_buffer: GreyscaleFrame<COLS, ROWS>,
+    back_buffer: GreyscaleFrame<COLS, ROWS>,
+    pending: bool,
     row_p: usize,
     brightness: Brightness,
+    orientation: Orientation,
+    enabled: bool,
 }
 
 impl<P, const ROWS: usize, const COLS: usize> LedMatrix<P, ROWS, COLS>
@@ -35,15 +124,21 @@ where
         LedMatrix {
             pin_rows,
             pin_cols,
-            frame_buffer: Frame::empty(),
+            frame_buffer: GreyscaleFrame::empty(),
+            back_buffer: GreyscaleFrame::empty(),
+            pending: false,
             row_p: 0,
             brightness: Default::default(),
+            orientation: Default::default(),
+            enabled: true,
         }
     }
 
-    /// Clear all LEDs
+    /// Clear all LEDs immediately
     pub fn clear(&mut self) {
         self.frame_buffer.clear();
+        self.back_buffer.clear();
+        self.pending = false;
         for row in self.pin_rows.iter_mut() {
             row.set_high().ok();
         }
@@ -53,19 +148,70 @@ where
         }
     }
 
-    /// Turn on point (x,y) in the frame buffer
+    /// Turn on point (x,y) in the back buffer
     pub fn on(&mut self, x: usize, y: usize) {
-        self.frame_buffer.set(x, y);
+        self.back_buffer.set(x, y, GreyscaleFrame::<COLS, ROWS>::MAX_LEVEL);
+        self.pending = true;
     }
 
-    /// Turn off point (x,y) in the frame buffer
+    /// Turn off point (x,y) in the back buffer
     pub fn off(&mut self, x: usize, y: usize) {
-        self.frame_buffer.unset(x, y);
+        self.back_buffer.set(x, y, 0);
+        self.pending = true;
+    }
+
+    /// Apply the provided frame onto the back buffer
+    pub fn apply(&mut self, frame: impl Into<GreyscaleFrame<COLS, ROWS>>) {
+        self.back_buffer = frame.into();
+        self.pending = true;
+    }
+
+    /// Keep refreshing the display until the changes made to the back buffer have been
+    /// displayed by a full scan of all rows. Returns immediately while the display is disabled.
+    pub async fn wait_for_frame_complete(&mut self) {
+        while self.enabled {
+            self.render().await;
+            if self.row_p == 0 && !self.pending {
+                break;
+            }
+        }
+    }
+
+    /// Turn off the display for low power operation, keeping the frame buffer.
+    ///
+    /// All rows and columns are driven low, so there is no voltage across the LEDs and no pin is
+    /// left floating. While disabled, [`render`](Self::render) does not touch the pins and only
+    /// waits for the refresh interval.
+    pub fn disable(&mut self) {
+        self.enabled = false;
+        self.power_down();
+        self.row_p = 0;
+    }
+
+    /// Turn the display back on after [`disable`](Self::disable), showing the preserved frame
+    /// from the next refresh.
+    pub fn enable(&mut self) {
+        self.enabled = true;
     }
 
-    /// Apply the provided frame onto the frame buffer
-    pub fn apply(&mut self, frame: Frame<COLS, ROWS>) {
-        self.frame_buffer = frame;
+    /// Check if the display is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Drive all pins to the lowest power state
+    fn power_down(&mut self) {
+        for pin in self.pin_rows.iter_mut().chain(self.pin_cols.iter_mut()) {
+            pin.set_low().ok();
+        }
+    }
+
+    // Replace the displayed frame with the back buffer if it was changed
+    fn swap_buffers(&mut self) {
+        if self.pending {
+            self.frame_buffer = self.back_buffer;
+            self.pending = false;
+        }
     }
 
     /// Adjust the brightness level
@@ -83,77 +229,208 @@ where
         self.brightness -= 1;
     }
 
-    /// Perform a full refresh of the display based on the current frame buffer
-    pub fn render(&mut self) {
+    /// Adjust the orientation, rotating all frames when displayed.
+    ///
+    /// Rotating by 90 or 270 degrees requires a square matrix.
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        if matches!(orientation, Orientation::Rotate90 | Orientation::Rotate270) {
+            assert!(ROWS == COLS);
+        }
+        self.orientation = orientation;
+    }
+
+    /// Return the current orientation
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Refresh the next row of the display based on the current frame buffer.
+    ///
+    /// The pins are not touched while the display is disabled.
+    ///
+    /// The row is refreshed for the refresh interval. Each LED is turned off once its on-time,
+    /// given by the brightness and its greyscale level, has passed. All waiting is done
+    /// asynchronously, so other tasks keep running while the display is lit.
+    pub async fn render(&mut self) {
+        let period = Duration::from_ticks(row_ticks(TICK_HZ));
+        if !self.enabled {
+            Timer::after(period).await;
+            return;
+        }
+
+        self.select_row();
+        self.light_row();
+
+        let mut lit = Duration::from_ticks(0);
+        for level in 1..=GreyscaleFrame::<COLS, ROWS>::MAX_LEVEL {
+            if self.row_levels().contains(&level) {
+                let on = self.on_time(level);
+                Timer::after(on - lit).await;
+                lit = on;
+                self.dim_columns(level);
+            }
+        }
+        Timer::after(period - lit).await;
+
+        self.next_row();
+    }
+
+    /// Time an LED at the provided greyscale level is lit during a row refresh
+    fn on_time(&self, level: u8) -> Duration {
+        Duration::from_ticks(level_ticks(self.brightness, TICK_HZ)[level as usize])
+    }
+
+    /// Levels of the current row, as displayed in the current orientation
+    fn row_levels(&self) -> [u8; COLS] {
+        core::array::from_fn(|x| {
+            let (x, y) = self.orientation.source(x, self.row_p, COLS, ROWS);
+            self.frame_buffer.get(x, y)
+        })
+    }
+
+    /// Turn off all rows and drive the columns for the current row from the frame buffer. Pending
+    /// changes are swapped in before the first row of a scan.
+    fn select_row(&mut self) {
+        if self.row_p == 0 {
+            self.swap_buffers();
+        }
+
         for row in self.pin_rows.iter_mut() {
             row.set_low().ok();
         }
 
+        let levels = self.row_levels();
         for (cid, col) in self.pin_cols.iter_mut().enumerate() {
-            if self.frame_buffer.is_set(cid, self.row_p) {
+            if levels[cid] > 0 {
                 col.set_low().ok();
             } else {
                 col.set_high().ok();
             }
         }
+    }
 
-        // Adjust interval will impact brightness of the LEDs
-        block_for(Duration::from_micros(
-            ((Brightness::MAX.level() - self.brightness.level()) as u64) * 6000 / Brightness::MAX.level() as u64,
-        ));
-
+    /// Turn on the current row
+    fn light_row(&mut self) {
         self.pin_rows[self.row_p].set_high().ok();
+    }
+
+    /// Turn off the columns of the current row at the provided greyscale level
+    fn dim_columns(&mut self, level: u8) {
+        let levels = self.row_levels();
+        for (cid, col) in self.pin_cols.iter_mut().enumerate() {
+            if levels[cid] == level {
+                col.set_high().ok();
+            }
+        }
+    }
 
+    /// Advance to the next row
+    fn next_row(&mut self) {
         self.row_p = (self.row_p + 1) % self.pin_rows.len();
     }
 
     /// Display the provided frame for the duration. Handles screen refresh
     /// in an async display loop.
-    pub async fn display(&mut self, frame: Frame<COLS, ROWS>, length: Duration) {
+    pub async fn display(&mut self, frame: impl Into<GreyscaleFrame<COLS, ROWS>>, length: Duration) {
         self.apply(frame);
         let end = Instant::now() + length;
         while Instant::now() < end {
-            self.render();
-            Timer::after(REFRESH_INTERVAL).await;
+            self.render().await;
         }
         self.clear();
     }
 
-    /// Scroll the provided text across the LED display using default duration based on text length
+    /// Scroll the provided text across the LED display at the default speed
     pub async fn scroll(&mut self, text: &str) {
-        self.scroll_with_speed(text, Duration::from_secs((text.len() / 2) as u64))
-            .await;
+        self.scroll_with_speed(text, DEFAULT_SCROLL_SPEED).await;
     }
 
-    /// Scroll the provided text across the screen within the provided duration
-    pub async fn scroll_with_speed(&mut self, text: &str, speed: Duration) {
-        self.animate(text.as_bytes(), AnimationEffect::Slide, speed).await;
+    /// Scroll the provided text across the screen at `speed` columns per second. A speed of 0 is
+    /// treated as 1 column per second.
+    ///
+    /// Characters use the real width of their glyph, separated by a single empty column. Use
+    /// [`Animation::scroll`] with [`play`](Self::play) for a different spacing.
+    pub async fn scroll_with_speed(&mut self, text: &str, speed: u32) {
+        self.scroll_with_font(text, &fonts::PENDOLINO, speed).await;
+    }
+
+    /// Scroll the provided text rendered in the font across the screen at `speed` columns per second.
+    /// A speed of 0 is treated as 1 column per second.
+    ///
+    /// Returns right away, leaving the display untouched, if the text has no columns to show.
+    pub async fn scroll_with_font(&mut self, text: &str, font: &dyn Font, speed: u32) {
+        if font.text_width(text, DEFAULT_SCROLL_GAP) == 0 {
+            return;
+        }
+        let mut animation: Animation<'_, COLS, ROWS> = Animation::scroll(text, font, DEFAULT_SCROLL_GAP, speed);
+        self.play(&mut animation).await;
+        self.clear();
+    }
+
+    /// Show a number, such as an integer or a [`Fixed`] point number. A single character is
+    /// shown for [`SHOW_NUMBER_DURATION`], longer numbers are scrolled across the screen.
+    pub async fn show_number(&mut self, value: impl fmt::Display) {
+        let mut text: heapless::String<32> = heapless::String::new();
+        // Numbers that do not fit are truncated
+        write!(text, "{}", value).ok();
+
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if COLS == 5 && ROWS == 5 => {
+                self.display(fonts::PENDOLINO.frame::<COLS, ROWS, 1>(c), SHOW_NUMBER_DURATION)
+                    .await
+            }
+            _ => self.scroll(&text).await,
+        }
+    }
+
+    /// Show a bar graph of the value relative to `max` like MakeCode's `plotBarGraph`.
+    ///
+    /// See [`Frame::bar_graph`].
+    pub fn plot_bar_graph(&mut self, value: u32, max: u32) {
+        self.apply(Frame::<COLS, ROWS>::bar_graph(value, max));
+    }
+
+    /// Show the value relative to `max` by lighting a proportional share of all LEDs.
+    ///
+    /// See [`Frame::dot_graph`].
+    pub fn plot_value(&mut self, value: u32, max: u32) {
+        self.apply(Frame::<COLS, ROWS>::dot_graph(value, max));
     }
 
     /// Apply animation based on data with the given effect during the provided duration
     pub async fn animate(&mut self, data: &[u8], effect: AnimationEffect, duration: Duration) {
         let mut animation: Animation<'_, COLS, ROWS> =
             Animation::new(AnimationData::Bytes(data), effect, duration).unwrap();
-        loop {
-            match animation.next(Instant::now()) {
-                AnimationState::Apply(f) => {
-                    self.apply(f);
-                }
-                AnimationState::Wait => {}
-                AnimationState::Done => {
-                    break;
-                }
-            }
-            self.render();
-            Timer::after(REFRESH_INTERVAL).await;
-        }
+        self.play(&mut animation).await;
         self.clear();
     }
 
     /// Animate a slice of frames using the provided effect during the provided duration
-    pub async fn animate_frames(&mut self, data: &[Frame<COLS, ROWS>], effect: AnimationEffect, duration: Duration) {
-        let mut animation: Animation<'_, COLS, ROWS> =
+    pub async fn animate_frames<const WORDS: usize>(
+        &mut self,
+        data: &[Frame<COLS, ROWS, WORDS>],
+        effect: AnimationEffect,
+        duration: Duration,
+    ) {
+        let mut animation: Animation<'_, COLS, ROWS, WORDS> =
             Animation::new(AnimationData::Frames(data), effect, duration).unwrap();
+        self.play(&mut animation).await;
+        self.clear();
+    }
+
+    /// Play the animation until it is finished or stopped.
+    ///
+    /// The last frame is kept in the frame buffer, but the display is only refreshed while
+    /// rendering, so it is no longer shown once this returns unless [`render`](Self::render) keeps
+    /// being called.
+    ///
+    /// The animation is only borrowed, so dropping this future (e.g. when a button is pressed)
+    /// leaves the animation at the current step, ready to be resumed, queried or stopped. Playing
+    /// it again continues with the next step right away, without catching up on the steps missed
+    /// in the meantime.
+    pub async fn play<const WORDS: usize>(&mut self, animation: &mut Animation<'_, COLS, ROWS, WORDS>) {
+        animation.restart_clock();
         loop {
             match animation.next(Instant::now()) {
                 AnimationState::Apply(f) => {
@@ -164,201 +441,168 @@ where
                     break;
                 }
             }
-            self.render();
-            Timer::after(REFRESH_INTERVAL).await;
+            self.render().await;
         }
-        self.clear();
     }
 
     /// Disassemble the `LedMatrix` and return the pins, as
     /// an array of row pins and an array of column pins.
-    pub fn into_inner(self) -> ([P; ROWS], [P;COLS]) {
+    pub fn into_inner(self) -> ([P; ROWS], [P; COLS]) {
         (self.pin_rows, self.pin_cols)
     }
 }
 
-/// An effect filter to apply for an animation
-#[derive(Clone, Copy)]
-pub enum AnimationEffect {
-    /// No effect
-    None,
-    /// Sliding effect
-    Slide,
-}
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
 
-enum AnimationData<'a, const XSIZE: usize, const YSIZE: usize> {
-    Frames(&'a [Frame<XSIZE, YSIZE>]),
-    Bytes(&'a [u8]),
-}
+    use super::mock::pins;
+    use super::*;
 
-impl<'a, const XSIZE: usize, const YSIZE: usize> AnimationData<'a, XSIZE, YSIZE> {
-    fn len(&self) -> usize {
-        match self {
-            AnimationData::Frames(f) => f.len(),
-            AnimationData::Bytes(f) => f.len(),
-        }
+    #[test]
+    fn test_greyscale_row() {
+        let rows = pins::<2>();
+        let cols = pins::<5>();
+        let mut matrix: LedMatrix<_, 2, 5> = LedMatrix::new(rows.clone(), cols.clone());
+        matrix.apply(GreyscaleFrame::new([[9, 3, 0, 3, 1], [0; 5]]));
+
+        matrix.select_row();
+        matrix.light_row();
+        assert!(rows[0].is_high());
+        assert!(!rows[1].is_high());
+        // Columns are active low
+        assert_eq!(cols.each_ref().map(|c| c.is_high()), [false, false, true, false, false]);
+
+        matrix.dim_columns(1);
+        assert_eq!(cols.each_ref().map(|c| c.is_high()), [false, false, true, false, true]);
+
+        matrix.dim_columns(3);
+        assert_eq!(cols.each_ref().map(|c| c.is_high()), [false, true, true, true, true]);
+
+        matrix.next_row();
+        matrix.select_row();
+        assert!(!rows[0].is_high());
+        assert!(cols.iter().all(|c| c.is_high()));
     }
 
-    fn frame(&self, idx: usize) -> Frame<XSIZE, YSIZE> {
-        match self {
-            AnimationData::Frames(f) => f[idx],
-            AnimationData::Bytes(f) => f[idx].into(),
+    #[test]
+    fn test_orientation() {
+        let rows = pins::<5>();
+        let cols = pins::<5>();
+        let mut matrix: LedMatrix<_, 5, 5> = LedMatrix::new(rows.clone(), cols.clone());
+        matrix.on(0, 0);
+        matrix.on(1, 0);
+        matrix.set_orientation(Orientation::Rotate90);
+
+        // The top row is displayed in the rightmost column
+        for y in 0..5 {
+            matrix.select_row();
+            for (x, col) in cols.iter().enumerate() {
+                assert_eq!(!col.is_high(), x == 4 && y < 2);
+            }
+            matrix.next_row();
         }
     }
-}
 
-struct Animation<'a, const XSIZE: usize, const YSIZE: usize> {
-    frames: AnimationData<'a, XSIZE, YSIZE>,
-    sequence: usize,
-    frame_index: usize,
-    index: usize,
-    length: usize,
-    effect: AnimationEffect,
-    wait: Duration,
-    next: Instant,
-}
-
-#[derive(PartialEq, Debug)]
-enum AnimationState<const XSIZE: usize, const YSIZE: usize> {
-    Wait,
-    Apply(Frame<XSIZE, YSIZE>),
-    Done,
-}
-
-impl<'a, const XSIZE: usize, const YSIZE: usize> Animation<'a, XSIZE, YSIZE> {
-    pub fn new(
-        frames: AnimationData<'a, XSIZE, YSIZE>,
-        effect: AnimationEffect,
-        duration: Duration,
-    ) -> Result<Self, AnimationError> {
-        assert!(frames.len() > 0);
-        let length = match effect {
-            AnimationEffect::Slide => frames.len() * XSIZE,
-            AnimationEffect::None => frames.len(),
-        };
-
-        if let Some(wait) = duration.checked_div(length as u32) {
-            Ok(Self {
-                frames,
-                frame_index: 0,
-                sequence: 0,
-                index: 0,
-                length,
-                effect,
-                wait,
-                next: Instant::now(),
-            })
-        } else {
-            Err(AnimationError::TooFast)
+    #[test]
+    fn test_double_buffer() {
+        let rows = pins::<3>();
+        let cols = pins::<3>();
+        let mut matrix: LedMatrix<_, 3, 3> = LedMatrix::new(rows.clone(), cols.clone());
+        matrix.apply(Frame::<3, 3>::from_image("111:111:111"));
+        matrix.select_row();
+        matrix.next_row();
+
+        // Changes during a scan are not displayed until the next scan starts
+        matrix.apply(Frame::<3, 3>::empty());
+        for _ in 1..3 {
+            matrix.select_row();
+            assert!(cols.iter().all(|c| !c.is_high()));
+            matrix.next_row();
         }
+        matrix.select_row();
+        assert!(cols.iter().all(|c| c.is_high()));
+
+        // Clearing takes effect immediately
+        matrix.apply(Frame::<3, 3>::from_image("111:111:111"));
+        matrix.clear();
+        matrix.next_row();
+        matrix.select_row();
+        assert!(cols.iter().all(|c| c.is_high()));
     }
-    fn current(&self) -> Frame<XSIZE, YSIZE> {
-        let mut current = self.frames.frame(self.frame_index);
-
-        let mut next = if self.frame_index < self.frames.len() - 1 {
-            self.frames.frame(self.frame_index + 1)
-        } else {
-            Frame::empty()
-        };
-
-        current.shift_left(self.sequence);
-        next.shift_right(XSIZE - self.sequence);
-
-        current.or(&next);
-        current
-    }
-
-    fn next(&mut self, now: Instant) -> AnimationState<XSIZE, YSIZE> {
-        if self.next <= now {
-            if self.index < self.length {
-                let current = self.current();
-                if self.sequence >= XSIZE - 1 {
-                    self.sequence = match self.effect {
-                        AnimationEffect::None => XSIZE,
-                        AnimationEffect::Slide => 0,
-                    };
-                    self.frame_index += 1;
-                } else {
-                    self.sequence += 1;
-                }
 
-                self.index += 1;
-                self.next += self.wait;
-                AnimationState::Apply(current)
-            } else {
-                AnimationState::Done
-            }
-        } else {
-            AnimationState::Wait
-        }
+    #[test]
+    fn test_disable() {
+        let rows = pins::<2>();
+        let cols = pins::<2>();
+        let mut matrix: LedMatrix<_, 2, 2> = LedMatrix::new(rows.clone(), cols.clone());
+        matrix.apply(Frame::<2, 2>::from_image("10:01"));
+        matrix.select_row();
+        matrix.light_row();
+        matrix.next_row();
+
+        matrix.disable();
+        assert!(!matrix.is_enabled());
+        assert!(rows.iter().chain(cols.iter()).all(|p| !p.is_high()));
+
+        // The frame is shown again from the first row
+        matrix.enable();
+        matrix.select_row();
+        matrix.light_row();
+        assert!(rows[0].is_high());
+        assert_eq!(cols.each_ref().map(|c| c.is_high()), [false, true]);
     }
-}
 
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-/// Errors produced when running animations
-pub enum AnimationError {
-    /// Animation scroll is too fast to keep up with the refresh rate
-    TooFast,
-}
+    #[test]
+    fn test_on_time() {
+        let mut matrix: LedMatrix<_, 5, 5> = LedMatrix::new(pins(), pins());
+        let max = GreyscaleFrame::<5, 5>::MAX_LEVEL;
+
+        matrix.set_brightness(Brightness::MAX);
+        assert_eq!(matrix.on_time(max), REFRESH_INTERVAL);
+        assert_eq!(matrix.on_time(0), Duration::from_ticks(0));
+        for level in 1..=max {
+            assert!(matrix.on_time(level) > matrix.on_time(level - 1));
+        }
 
-/*
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let full = matrix.on_time(max);
+        matrix.set_brightness(Brightness::MIN);
+        assert!(matrix.on_time(max) > Duration::from_ticks(0));
+        assert!(matrix.on_time(max) < full / 5);
+
+        matrix.set_brightness(Brightness::DEFAULT);
+        assert!(matrix.on_time(max) < full);
+        assert!(matrix.on_time(max) > full / 2);
+    }
 
     #[test]
-    fn test_animation() {
-        let mut animation: Animation<5, 5> = Animation::new(
-            AnimationData::Bytes(b"12"),
-            AnimationEffect::Slide,
-            Duration::from_secs(1),
-        )
-        .unwrap();
-
-        let expected = animation.length;
-        let mut n = 0;
-        while n < expected {
-            if let AnimationState::Apply(c) =
-                animation.next(Instant::now() + Duration::from_secs(1))
-            {
-                println!("C ({}): \n{:#?}", n, c);
-                n += 1;
-            } else {
-                break;
-            }
+    fn test_on_time_rtc() {
+        // All levels are visible and distinct with the 32768 Hz RTC time driver
+        let period = row_ticks(32768);
+        assert!(period >= 64);
+        for brightness in Brightness::MIN.level()..=Brightness::MAX.level() {
+            let ticks = level_ticks(Brightness::new(brightness), 32768);
+            assert_eq!(ticks[0], 0);
+            assert!(ticks.windows(2).all(|w| w[0] < w[1]));
+            assert!(ticks[ticks.len() - 1] <= period);
         }
-        assert!(animation.next(Instant::now() + Duration::from_secs(1)) == AnimationState::Done);
+        assert_eq!(level_ticks(Brightness::MAX, 32768)[9], period);
+        assert_eq!(level_ticks(Brightness::MIN, 32768), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
     }
 
     #[test]
-    fn test_animation_length() {
-        let animation: Animation<5, 5> = Animation::new(
-            AnimationData::Bytes(b"12"),
-            AnimationEffect::Slide,
-            Duration::from_secs(1),
-        )
-        .unwrap();
-
-        assert_eq!(animation.length, 10);
-
-        let animation: Animation<5, 5> = Animation::new(
-            AnimationData::Bytes(b"123"),
-            AnimationEffect::Slide,
-            Duration::from_secs(1),
-        )
-        .unwrap();
-
-        assert_eq!(animation.length, 15);
-
-        let animation: Animation<5, 5> = Animation::new(
-            AnimationData::Bytes(b"1234"),
-            AnimationEffect::Slide,
-            Duration::from_secs(1),
-        )
-        .unwrap();
-
-        assert_eq!(animation.length, 20);
+    fn test_scroll_empty() {
+        let rows = pins::<5>();
+        let cols = pins::<5>();
+        let mut matrix: LedMatrix<_, 5, 5> = LedMatrix::new(rows.clone(), cols.clone());
+        matrix.on(2, 2);
+
+        // Completes without waiting for a single refresh, and keeps the displayed frame
+        assert!(matrix
+            .scroll_with_font("", &fonts::PENDOLINO, 10)
+            .now_or_never()
+            .is_some());
+        assert_eq!(matrix.back_buffer.get(2, 2), GreyscaleFrame::<5, 5>::MAX_LEVEL);
+        assert!(rows.iter().all(|r| !r.is_high()));
     }
 }
-*/
@@ -0,0 +1,492 @@
+//! Background refresh of a LED matrix from a shared frame buffer
+//!
+//! A [`Refresher`] owns the [`LedMatrix`] and keeps scanning the contents of a [`SharedFrame`]
+//! from a dedicated task, so the image stays lit while other tasks are busy. Any task holding a
+//! reference to the [`SharedFrame`] can update the image or the brightness, wait for the update
+//! to be fully displayed with [`SharedFrame::wait_for_frame_complete`], or measure the ambient
+//! light with [`SharedFrame::read_light_level`] if the refresher senses light.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use microbit_bsp::display::{fonts, SharedFrame};
+//! use microbit_bsp::{DisplayRefresher, Microbit, SharedDisplay};
+//!
+//! static DISPLAY: SharedDisplay = SharedFrame::new();
+//!
+//! // Run from a dedicated task, e.g. one spawned with `embassy_executor`
+//! async fn refresh(mut refresher: DisplayRefresher) -> ! {
+//!     refresher.run().await
+//! }
+//!
+//! let board = Microbit::default();
+//! let refresher = DisplayRefresher::with_light_sense(board.display, &DISPLAY);
+//! DISPLAY.apply(fonts::CHECK_MARK);
+//!
+//! async fn brightness() -> Option<u8> {
+//!     DISPLAY.read_light_level().await
+//! }
+//! ```
+use core::cell::{Cell, RefCell};
+use core::future::poll_fn;
+use core::task::Poll;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::signal::Signal;
+use embassy_sync::waitqueue::MultiWakerRegistration;
+use embassy_time::Timer;
+use embedded_hal::digital::OutputPin;
+
+use super::{Brightness, GreyscaleFrame, LedMatrix, SensePin, LIGHT_SENSE_CHARGE};
+
+#[derive(Clone, Copy)]
+struct State<const XSIZE: usize, const YSIZE: usize> {
+    frame: GreyscaleFrame<XSIZE, YSIZE>,
+    brightness: Brightness,
+    enabled: bool,
+    // Number of full scans completed, wrapping around
+    scans: u32,
+    // A light measurement is requested, or in progress
+    light_request: bool,
+    sensing: bool,
+    // Number of light measurements completed, wrapping around, and the last result
+    light_reads: u32,
+    light: Option<u8>,
+}
+
+// Number of tasks waiting for a scan or a light measurement that are woken individually. More
+// waiters are supported, at the cost of waking all of them early.
+const WAITERS: usize = 4;
+
+/// A frame buffer that can be shared between tasks and displayed by a [`Refresher`].
+pub struct SharedFrame<const XSIZE: usize, const YSIZE: usize> {
+    state: Mutex<CriticalSectionRawMutex, Cell<State<XSIZE, YSIZE>>>,
+    waiters: Mutex<CriticalSectionRawMutex, RefCell<MultiWakerRegistration<WAITERS>>>,
+    wake: Signal<CriticalSectionRawMutex, ()>,
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> SharedFrame<XSIZE, YSIZE> {
+    /// Create an empty shared frame buffer with default brightness
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(Cell::new(State {
+                frame: GreyscaleFrame::empty(),
+                brightness: Brightness::DEFAULT,
+                enabled: true,
+                scans: 0,
+                light_request: false,
+                sensing: false,
+                light_reads: 0,
+                light: None,
+            })),
+            waiters: Mutex::new(RefCell::new(MultiWakerRegistration::new())),
+            wake: Signal::new(),
+        }
+    }
+
+    fn modify(&self, f: impl FnOnce(&mut State<XSIZE, YSIZE>)) {
+        self.state.lock(|s| {
+            let mut state = s.get();
+            f(&mut state);
+            s.set(state);
+        });
+    }
+
+    /// Replace the displayed frame
+    pub fn apply(&self, frame: impl Into<GreyscaleFrame<XSIZE, YSIZE>>) {
+        let frame = frame.into();
+        self.modify(|s| s.frame = frame);
+    }
+
+    /// Modify the displayed frame in place
+    pub fn update(&self, f: impl FnOnce(&mut GreyscaleFrame<XSIZE, YSIZE>)) {
+        self.modify(|s| f(&mut s.frame));
+    }
+
+    /// Return a copy of the displayed frame
+    pub fn frame(&self) -> GreyscaleFrame<XSIZE, YSIZE> {
+        self.state.lock(|s| s.get().frame)
+    }
+
+    /// Clear all LEDs
+    pub fn clear(&self) {
+        self.modify(|s| s.frame.clear());
+    }
+
+    /// Turn on point (x,y)
+    pub fn on(&self, x: usize, y: usize) {
+        self.modify(|s| s.frame.set(x, y, GreyscaleFrame::<XSIZE, YSIZE>::MAX_LEVEL));
+    }
+
+    /// Turn off point (x,y)
+    pub fn off(&self, x: usize, y: usize) {
+        self.modify(|s| s.frame.set(x, y, 0));
+    }
+
+    /// Adjust the brightness level
+    pub fn set_brightness(&self, brightness: Brightness) {
+        self.modify(|s| s.brightness = brightness);
+    }
+
+    /// Return the current brightness level
+    pub fn brightness(&self) -> Brightness {
+        self.state.lock(|s| s.get().brightness)
+    }
+
+    /// Turn off the display and park the refresher until the display is enabled again, see
+    /// [`LedMatrix::disable`]. The frame is preserved, and can still be updated while disabled.
+    pub fn disable(&self) {
+        self.modify(|s| s.enabled = false);
+        // Wake up tasks waiting for a scan that will not happen
+        self.wake_waiters();
+    }
+
+    /// Turn the display back on after [`disable`](Self::disable)
+    pub fn enable(&self) {
+        self.modify(|s| s.enabled = true);
+        self.wake.signal(());
+    }
+
+    /// Check if the display is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.state.lock(|s| s.get().enabled)
+    }
+
+    /// Wait until the current contents have been displayed by a full scan of the refresher.
+    /// Returns immediately while the display is disabled. Any number of tasks can wait at the
+    /// same time.
+    ///
+    /// The scan in progress may have started before the last update, so this waits for it to
+    /// complete followed by another full scan.
+    pub async fn wait_for_frame_complete(&self) {
+        let start = self.state.lock(|s| s.get().scans);
+        poll_fn(|cx| {
+            self.state.lock(|s| {
+                let state = s.get();
+                if !state.enabled || state.scans.wrapping_sub(start) >= 2 {
+                    return Poll::Ready(());
+                }
+                // Registered while holding the state, so a scan completing now wakes the task
+                self.waiters.lock(|w| w.borrow_mut().register(cx.waker()));
+                Poll::Pending
+            })
+        })
+        .await
+    }
+
+    /// Measure the ambient light level with the LEDs, from 0 (dark) to 255 (bright). Returns
+    /// `None` if the refresher was not created with [`Refresher::with_light_sense`].
+    ///
+    /// The refresher measures between two rows, even while the display is disabled. The display
+    /// is dark during the measurement, which takes up to
+    /// [`LIGHT_SENSE_TIMEOUT`](super::LIGHT_SENSE_TIMEOUT). Tasks requesting a level at the same
+    /// time share the same measurement.
+    pub async fn read_light_level(&self) -> Option<u8> {
+        // A measurement in progress may have started before the request, so wait for the next one
+        let target = self.state.lock(|s| {
+            let mut state = s.get();
+            state.light_request = true;
+            s.set(state);
+            state.light_reads.wrapping_add(1 + state.sensing as u32)
+        });
+        self.wake.signal(());
+        poll_fn(|cx| {
+            self.state.lock(|s| {
+                let state = s.get();
+                if state.light_reads.wrapping_sub(target) as i32 >= 0 {
+                    return Poll::Ready(state.light);
+                }
+                self.waiters.lock(|w| w.borrow_mut().register(cx.waker()));
+                Poll::Pending
+            })
+        })
+        .await
+    }
+
+    // Start a requested light measurement, returning false if there is none
+    fn start_light_sense(&self) -> bool {
+        self.state.lock(|s| {
+            let mut state = s.get();
+            let requested = state.light_request;
+            state.light_request = false;
+            state.sensing = requested;
+            s.set(state);
+            requested
+        })
+    }
+
+    fn finish_light_sense(&self, light: Option<u8>) {
+        self.modify(|s| {
+            s.sensing = false;
+            s.light = light;
+            s.light_reads = s.light_reads.wrapping_add(1);
+        });
+        self.wake_waiters();
+    }
+
+    fn wake_waiters(&self) {
+        self.waiters.lock(|w| w.borrow_mut().wake());
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> Default for SharedFrame<XSIZE, YSIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Refresh engine continuously scanning a [`SharedFrame`] onto a [`LedMatrix`].
+///
+/// The matrix pins must also be readable as inputs, see [`SensePin`], to measure the ambient
+/// light with [`with_light_sense`](Self::with_light_sense).
+pub struct Refresher<'a, P, const ROWS: usize, const COLS: usize>
+where
+    P: OutputPin + 'static,
+{
+    matrix: LedMatrix<P, ROWS, COLS>,
+    shared: &'a SharedFrame<COLS, ROWS>,
+    light_sense: bool,
+}
+
+impl<'a, P, const ROWS: usize, const COLS: usize> Refresher<'a, P, ROWS, COLS>
+where
+    P: SensePin,
+{
+    /// Create a refresher driving the matrix from the shared frame buffer. Light levels
+    /// requested through the shared frame are reported as `None`, see
+    /// [`with_light_sense`](Self::with_light_sense).
+    pub fn new(matrix: LedMatrix<P, ROWS, COLS>, shared: &'a SharedFrame<COLS, ROWS>) -> Self {
+        Self {
+            matrix,
+            shared,
+            light_sense: false,
+        }
+    }
+
+    /// Create a refresher driving the matrix from the shared frame buffer, which also measures
+    /// the light levels requested with [`SharedFrame::read_light_level`] between two rows
+    pub fn with_light_sense(matrix: LedMatrix<P, ROWS, COLS>, shared: &'a SharedFrame<COLS, ROWS>) -> Self {
+        Self {
+            light_sense: true,
+            ..Self::new(matrix, shared)
+        }
+    }
+
+    fn sync(&mut self) {
+        let state = self.shared.state.lock(|s| s.get());
+        self.matrix.apply(state.frame);
+        self.matrix.set_brightness(state.brightness);
+    }
+
+    // Notify waiting tasks when the last row of a scan has been displayed
+    fn row_complete(&self) {
+        if self.matrix.row_p == 0 {
+            self.shared.modify(|s| s.scans = s.scans.wrapping_add(1));
+            self.shared.wake_waiters();
+        }
+    }
+
+    // Measure the light level if a task requested it through the shared frame
+    async fn sense_light(&mut self) {
+        if !self.shared.start_light_sense() {
+            return;
+        }
+        let light = if self.light_sense {
+            self.matrix.charge();
+            Timer::after(LIGHT_SENSE_CHARGE).await;
+            Some(self.matrix.measure_discharge().await)
+        } else {
+            None
+        };
+        self.shared.finish_light_sense(light);
+    }
+
+    /// Refresh the next row of the display from the shared frame buffer, then measure the light
+    /// level if it was requested with [`SharedFrame::read_light_level`].
+    ///
+    /// While the shared frame is disabled, the matrix is powered down and this waits until it
+    /// is enabled again, without any periodic wakeups apart from light level requests.
+    pub async fn refresh(&mut self) {
+        if !self.shared.is_enabled() {
+            self.matrix.disable();
+            while !self.shared.is_enabled() {
+                self.sense_light().await;
+                self.shared.wake.wait().await;
+            }
+            self.matrix.enable();
+        }
+        self.sync();
+        self.matrix.render().await;
+        self.row_complete();
+        self.sense_light().await;
+    }
+
+    /// Keep refreshing the display forever. Intended to run in a dedicated task.
+    pub async fn run(&mut self) -> ! {
+        loop {
+            self.refresh().await;
+        }
+    }
+
+    /// Stop refreshing, turn off all LEDs and return the matrix
+    pub fn into_inner(mut self) -> LedMatrix<P, ROWS, COLS> {
+        self.matrix.clear();
+        self.matrix
+    }
+
+    /// Measure the ambient light level between two row refreshes, see
+    /// [`LedMatrix::read_light_level`]. Only usable while the refresher is not running, use
+    /// [`SharedFrame::read_light_level`] from other tasks instead.
+    pub async fn read_light_level(&mut self) -> u8 {
+        self.matrix.read_light_level().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::Context;
+
+    use futures::task::noop_waker_ref;
+    use futures::FutureExt;
+
+    use super::*;
+    use crate::display::fonts::CROSS_MARK;
+    use crate::display::mock::{pins, MockPin};
+
+    // Refresh a row without waiting
+    fn scan<const ROWS: usize, const COLS: usize>(refresher: &mut Refresher<'_, MockPin, ROWS, COLS>) {
+        refresher.sync();
+        refresher.matrix.select_row();
+        refresher.matrix.light_row();
+        refresher.matrix.next_row();
+        refresher.row_complete();
+    }
+
+    #[test]
+    fn test_scan_shared_frame() {
+        let rows = pins::<5>();
+        let cols = pins::<5>();
+        let shared = SharedFrame::new();
+        let mut refresher = Refresher::new(LedMatrix::new(rows.clone(), cols.clone()), &shared);
+
+        shared.apply(CROSS_MARK);
+        for y in 0..5 {
+            scan(&mut refresher);
+            for (r, row) in rows.iter().enumerate() {
+                assert_eq!(row.is_high(), r == y);
+            }
+            for (x, col) in cols.iter().enumerate() {
+                // Columns are active low
+                assert_eq!(!col.is_high(), CROSS_MARK.is_set(x, y));
+            }
+        }
+
+        // Updates through the shared buffer show up on the next scan
+        shared.clear();
+        shared.on(4, 0);
+        scan(&mut refresher);
+        assert!(rows[0].is_high());
+        assert!(cols[..4].iter().all(|c| c.is_high()));
+        assert!(!cols[4].is_high());
+    }
+
+    #[test]
+    fn test_shared_brightness() {
+        let shared: SharedFrame<5, 5> = SharedFrame::new();
+        let mut refresher = Refresher::new(LedMatrix::new(pins::<5>(), pins::<5>()), &shared);
+        assert_eq!(shared.brightness(), Brightness::DEFAULT);
+
+        shared.set_brightness(Brightness::MAX);
+        refresher.sync();
+        assert_eq!(refresher.matrix.brightness, Brightness::MAX);
+    }
+
+    #[test]
+    fn test_scan_complete() {
+        let shared: SharedFrame<5, 3> = SharedFrame::new();
+        let mut refresher = Refresher::new(LedMatrix::new(pins::<3>(), pins::<5>()), &shared);
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        // Every waiting task sees the scans complete, starting with the scan in progress
+        let mut first = pin!(shared.wait_for_frame_complete());
+        let mut second = pin!(shared.wait_for_frame_complete());
+        for _ in 0..5 {
+            assert!(first.as_mut().poll(&mut cx).is_pending());
+            assert!(second.as_mut().poll(&mut cx).is_pending());
+            scan(&mut refresher);
+        }
+        let mut third = pin!(shared.wait_for_frame_complete());
+        assert!(third.as_mut().poll(&mut cx).is_pending());
+        scan(&mut refresher);
+        assert!(first.as_mut().poll(&mut cx).is_ready());
+        assert!(second.as_mut().poll(&mut cx).is_ready());
+
+        // Waiting during a scan takes another full scan
+        for _ in 0..3 {
+            assert!(third.as_mut().poll(&mut cx).is_pending());
+            scan(&mut refresher);
+        }
+        assert!(third.as_mut().poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn test_shared_disable() {
+        let shared: SharedFrame<5, 5> = SharedFrame::new();
+        assert!(shared.is_enabled());
+
+        shared.disable();
+        assert!(!shared.is_enabled());
+        assert!(shared.wait_for_frame_complete().now_or_never().is_some());
+
+        // The frame can be updated while disabled
+        shared.apply(CROSS_MARK);
+        shared.enable();
+        assert!(shared.is_enabled());
+        assert!(shared.wake.signaled());
+        assert_eq!(shared.frame(), CROSS_MARK.into());
+    }
+
+    #[test]
+    fn test_light_request() {
+        let shared: SharedFrame<5, 5> = SharedFrame::new();
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(!shared.start_light_sense());
+
+        let mut first = pin!(shared.read_light_level());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(shared.start_light_sense());
+
+        // Requests made during a measurement wait for the next one
+        let mut second = pin!(shared.read_light_level());
+        let mut third = pin!(shared.read_light_level());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        shared.finish_light_sense(Some(10));
+        assert_eq!(first.as_mut().poll(&mut cx), Poll::Ready(Some(10)));
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert!(third.as_mut().poll(&mut cx).is_pending());
+
+        assert!(shared.start_light_sense());
+        shared.finish_light_sense(Some(20));
+        assert_eq!(second.as_mut().poll(&mut cx), Poll::Ready(Some(20)));
+        assert_eq!(third.as_mut().poll(&mut cx), Poll::Ready(Some(20)));
+        assert!(!shared.start_light_sense());
+    }
+
+    #[test]
+    fn test_light_without_sense() {
+        let shared: SharedFrame<5, 5> = SharedFrame::new();
+        let mut refresher = Refresher::new(LedMatrix::new(pins::<5>(), pins::<5>()), &shared);
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        let mut request = pin!(shared.read_light_level());
+        assert!(request.as_mut().poll(&mut cx).is_pending());
+        assert!(refresher.sense_light().now_or_never().is_some());
+        assert_eq!(request.as_mut().poll(&mut cx), Poll::Ready(None));
+
+        let refresher = Refresher::with_light_sense(LedMatrix::new(pins::<5>(), pins::<5>()), &shared);
+        assert!(refresher.light_sense);
+    }
+}
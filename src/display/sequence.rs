@@ -0,0 +1,499 @@
+//! Binary format for frame sequences with per-frame durations
+//!
+//! Sequences designed with external tools can be embedded with `include_bytes!` and played
+//! without copying them to RAM, using [`Animation::sequence`](super::Animation::sequence). All
+//! numbers are little endian.
+//!
+//! | Offset | Size | Content                                                   |
+//! |--------|------|-----------------------------------------------------------|
+//! | 0      | 4    | Magic `MBFS`                                              |
+//! | 4      | 1    | Format version, currently 1                               |
+//! | 5      | 1    | Width of the frames in LEDs                               |
+//! | 6      | 1    | Height of the frames in LEDs                              |
+//! | 7      | 1    | Bits per LED, 1 for on/off or 4 for greyscale levels 0-9  |
+//! | 8      | 2    | Number of frames                                          |
+//! | 10     |      | Frames                                                    |
+//!
+//! Each frame starts with its duration in milliseconds as 2 bytes, followed by the LEDs in
+//! reading order, packed starting at the most significant bit of each byte. Frames are padded to
+//! whole bytes.
+//!
+//! # Examples
+//!
+//! ```
+//! use microbit_bsp::display::{encode_sequence, Animation, Depth, FrameSequence, GreyscaleFrame};
+//! use microbit_bsp::embassy_time::Duration;
+//!
+//! let frames = [
+//!     (GreyscaleFrame::<5, 5>::from_image("00000:00000:00900:00000:00000"), Duration::from_millis(100)),
+//!     (GreyscaleFrame::<5, 5>::from_image("00000:05550:05950:05550:00000"), Duration::from_millis(200)),
+//! ];
+//! let mut data = [0; 64];
+//! let len = encode_sequence(&frames, Depth::Greyscale, &mut data).unwrap();
+//!
+//! let sequence = FrameSequence::parse(&data[..len]).unwrap();
+//! assert_eq!(sequence.len(), 2);
+//! let animation: Animation<'_, 5, 5> = Animation::sequence(sequence).unwrap();
+//! ```
+use core::fmt;
+
+use embassy_time::Duration;
+
+use super::{Frame, GreyscaleFrame};
+
+/// Magic bytes at the start of a frame sequence
+pub const SEQUENCE_MAGIC: [u8; 4] = *b"MBFS";
+
+/// Version of the frame sequence format
+pub const SEQUENCE_VERSION: u8 = 1;
+
+// Length of the sequence header and the header of each frame
+const HEADER_LEN: usize = 10;
+const FRAME_HEADER_LEN: usize = 2;
+
+/// Number of bits stored for each LED
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Depth {
+    /// LEDs are on or off
+    Binary = 1,
+    /// LEDs have a greyscale level from 0 to 9
+    Greyscale = 4,
+}
+
+impl Depth {
+    /// Number of bits per LED
+    pub const fn bits(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Errors produced when parsing or encoding frame sequences
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SequenceError {
+    /// The data does not start with [`SEQUENCE_MAGIC`]
+    Magic,
+    /// The format version is not supported
+    Version(u8),
+    /// The number of bits per LED is not supported
+    Depth(u8),
+    /// The data does not have the length given by the header, or the output buffer is too small
+    Length {
+        /// Length required for the sequence
+        expected: usize,
+        /// Length of the data or buffer
+        actual: usize,
+    },
+    /// A greyscale level is above the highest level
+    Level {
+        /// Index of the frame with the invalid level
+        frame: usize,
+    },
+    /// A frame duration does not fit in the format
+    Duration {
+        /// Index of the frame with the invalid duration
+        frame: usize,
+    },
+    /// The frames are too large for the format
+    Size,
+    /// The sequence has no frames
+    Empty,
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceError::Magic => write!(f, "not a frame sequence"),
+            SequenceError::Version(v) => write!(f, "unsupported version {}", v),
+            SequenceError::Depth(d) => write!(f, "unsupported depth of {} bits", d),
+            SequenceError::Length { expected, actual } => {
+                write!(f, "expected {} bytes, found {}", expected, actual)
+            }
+            SequenceError::Level { frame } => write!(f, "invalid level in frame {}", frame),
+            SequenceError::Duration { frame } => write!(f, "duration of frame {} is too long", frame),
+            SequenceError::Size => write!(f, "frames are too large"),
+            SequenceError::Empty => write!(f, "no frames"),
+        }
+    }
+}
+
+/// Number of bytes of an encoded sequence with the frame size, depth and number of frames
+pub const fn sequence_len(width: usize, height: usize, depth: Depth, count: usize) -> usize {
+    HEADER_LEN + count * frame_len(width, height, depth)
+}
+
+// Number of bytes of a frame, including its header
+const fn frame_len(width: usize, height: usize, depth: Depth) -> usize {
+    FRAME_HEADER_LEN + (width * height * depth.bits()).div_ceil(8)
+}
+
+/// A sequence of frames parsed from encoded data, borrowing the data without copying it
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameSequence<'a> {
+    frames: &'a [u8],
+    width: usize,
+    height: usize,
+    depth: Depth,
+    count: usize,
+}
+
+impl<'a> FrameSequence<'a> {
+    /// Parse and validate an encoded frame sequence
+    pub fn parse(data: &'a [u8]) -> Result<Self, SequenceError> {
+        if data.len() < HEADER_LEN {
+            return Err(SequenceError::Length {
+                expected: HEADER_LEN,
+                actual: data.len(),
+            });
+        }
+        if data[..4] != SEQUENCE_MAGIC {
+            return Err(SequenceError::Magic);
+        }
+        if data[4] != SEQUENCE_VERSION {
+            return Err(SequenceError::Version(data[4]));
+        }
+        let depth = match data[7] {
+            1 => Depth::Binary,
+            4 => Depth::Greyscale,
+            d => return Err(SequenceError::Depth(d)),
+        };
+        let (width, height) = (data[5] as usize, data[6] as usize);
+        let count = u16::from_le_bytes([data[8], data[9]]) as usize;
+        if count == 0 {
+            return Err(SequenceError::Empty);
+        }
+
+        let expected = sequence_len(width, height, depth, count);
+        if data.len() != expected {
+            return Err(SequenceError::Length {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        let sequence = Self {
+            frames: &data[HEADER_LEN..],
+            width,
+            height,
+            depth,
+            count,
+        };
+        if depth == Depth::Greyscale {
+            let max = GreyscaleFrame::<1, 1>::MAX_LEVEL;
+            for (i, frame) in sequence.iter().enumerate() {
+                if frame.levels().any(|level| level > max) {
+                    return Err(SequenceError::Level { frame: i });
+                }
+            }
+        }
+        Ok(sequence)
+    }
+
+    /// Width of the frames in LEDs
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of the frames in LEDs
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of bits stored for each LED
+    pub fn depth(&self) -> Depth {
+        self.depth
+    }
+
+    /// Number of frames
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Check if the sequence has no frames
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Return the frame with the index
+    pub fn get(&self, index: usize) -> Option<SequenceFrame<'a>> {
+        if index >= self.count {
+            return None;
+        }
+        let len = frame_len(self.width, self.height, self.depth);
+        let data = &self.frames[index * len..(index + 1) * len];
+        Some(SequenceFrame {
+            duration_ms: u16::from_le_bytes([data[0], data[1]]),
+            pixels: &data[FRAME_HEADER_LEN..],
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+        })
+    }
+
+    /// Iterate over the frames
+    pub fn iter(&self) -> impl Iterator<Item = SequenceFrame<'a>> + '_ {
+        (0..self.count).filter_map(|i| self.get(i))
+    }
+
+    /// Sum of the durations of all frames
+    pub fn duration(&self) -> Duration {
+        self.iter()
+            .fold(Duration::from_ticks(0), |total, f| total + f.duration())
+    }
+}
+
+/// A single frame of a [`FrameSequence`]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SequenceFrame<'a> {
+    duration_ms: u16,
+    pixels: &'a [u8],
+    width: usize,
+    height: usize,
+    depth: Depth,
+}
+
+impl SequenceFrame<'_> {
+    /// Time the frame is displayed
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms as u64)
+    }
+
+    /// Greyscale level of the LED at (x, y), set LEDs of binary frames being at the highest level
+    pub fn level(&self, x: usize, y: usize) -> u8 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
+        let bits = self.depth.bits();
+        let bit = (y * self.width + x) * bits;
+        let value = (self.pixels[bit / 8] >> (8 - bits - bit % 8)) & ((1 << bits) - 1);
+        match self.depth {
+            Depth::Binary => value * GreyscaleFrame::<1, 1>::MAX_LEVEL,
+            Depth::Greyscale => value,
+        }
+    }
+
+    // Levels of all LEDs in reading order
+    fn levels(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| self.level(x, y)))
+    }
+
+    /// Convert to a greyscale frame. LEDs outside the sequence frame are off.
+    pub fn to_greyscale<const XSIZE: usize, const YSIZE: usize>(&self) -> GreyscaleFrame<XSIZE, YSIZE> {
+        let mut frame = GreyscaleFrame::empty();
+        for y in 0..YSIZE {
+            for x in 0..XSIZE {
+                frame.set(x, y, self.level(x, y));
+            }
+        }
+        frame
+    }
+
+    /// Convert to a frame with all LEDs above level 0 set
+    pub fn to_frame<const XSIZE: usize, const YSIZE: usize, const WORDS: usize>(&self) -> Frame<XSIZE, YSIZE, WORDS> {
+        let mut frame = Frame::empty();
+        for y in 0..YSIZE {
+            for x in 0..XSIZE {
+                if self.level(x, y) > 0 {
+                    frame.set(x, y);
+                }
+            }
+        }
+        frame
+    }
+}
+
+/// Encode the frames with their durations into the output buffer, returning the number of bytes
+/// written. Levels are stored as on/off for [`Depth::Binary`].
+pub fn encode_sequence<const XSIZE: usize, const YSIZE: usize>(
+    frames: &[(GreyscaleFrame<XSIZE, YSIZE>, Duration)],
+    depth: Depth,
+    out: &mut [u8],
+) -> Result<usize, SequenceError> {
+    if XSIZE > u8::MAX as usize || YSIZE > u8::MAX as usize || frames.len() > u16::MAX as usize {
+        return Err(SequenceError::Size);
+    }
+    if frames.is_empty() {
+        return Err(SequenceError::Empty);
+    }
+    let len = sequence_len(XSIZE, YSIZE, depth, frames.len());
+    if out.len() < len {
+        return Err(SequenceError::Length {
+            expected: len,
+            actual: out.len(),
+        });
+    }
+
+    out[..4].copy_from_slice(&SEQUENCE_MAGIC);
+    out[4] = SEQUENCE_VERSION;
+    out[5] = XSIZE as u8;
+    out[6] = YSIZE as u8;
+    out[7] = depth.bits() as u8;
+    out[8..HEADER_LEN].copy_from_slice(&(frames.len() as u16).to_le_bytes());
+
+    let flen = frame_len(XSIZE, YSIZE, depth);
+    let bits = depth.bits();
+    for (i, (frame, duration)) in frames.iter().enumerate() {
+        let ms = u16::try_from(duration.as_millis()).map_err(|_| SequenceError::Duration { frame: i })?;
+        let data = &mut out[HEADER_LEN + i * flen..HEADER_LEN + (i + 1) * flen];
+        data.fill(0);
+        data[..FRAME_HEADER_LEN].copy_from_slice(&ms.to_le_bytes());
+
+        let pixels = &mut data[FRAME_HEADER_LEN..];
+        for y in 0..YSIZE {
+            for x in 0..XSIZE {
+                let level = frame.get(x, y);
+                let value = match depth {
+                    Depth::Binary => (level > 0) as u8,
+                    Depth::Greyscale => level,
+                };
+                let bit = (y * XSIZE + x) * bits;
+                pixels[bit / 8] |= value << (8 - bits - bit % 8);
+            }
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::fonts::{HEART, HEART_SMALL};
+
+    fn frames() -> [(GreyscaleFrame<5, 5>, Duration); 3] {
+        [
+            (HEART.into(), Duration::from_millis(500)),
+            (
+                GreyscaleFrame::from_image("01234:56789:00000:98765:43210"),
+                Duration::from_millis(20),
+            ),
+            (HEART_SMALL.into(), Duration::from_millis(1500)),
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut data = [0; 64];
+        let len = encode_sequence(&frames(), Depth::Greyscale, &mut data).unwrap();
+        assert_eq!(len, sequence_len(5, 5, Depth::Greyscale, 3));
+        assert_eq!(len, 10 + 3 * 15);
+
+        let sequence = FrameSequence::parse(&data[..len]).unwrap();
+        assert_eq!((sequence.width(), sequence.height()), (5, 5));
+        assert_eq!(sequence.depth(), Depth::Greyscale);
+        assert_eq!(sequence.len(), 3);
+        assert_eq!(sequence.duration(), Duration::from_millis(2020));
+        for (frame, (expected, duration)) in sequence.iter().zip(frames()) {
+            assert_eq!(frame.to_greyscale::<5, 5>(), expected);
+            assert_eq!(frame.duration(), duration);
+        }
+        assert!(sequence.get(3).is_none());
+    }
+
+    #[test]
+    fn test_binary() {
+        let mut data = [0; 32];
+        let len = encode_sequence(&frames(), Depth::Binary, &mut data).unwrap();
+        assert_eq!(len, 10 + 3 * 6);
+        assert_eq!(&data[..10], b"MBFS\x01\x05\x05\x01\x03\x00");
+        // 500 ms, then the rows of the heart: 01010 11111 11111 01110 00100
+        assert_eq!(
+            &data[10..16],
+            &[0xf4, 0x01, 0b0101_0111, 0b1111_1110, 0b1110_0010, 0b0000_0000]
+        );
+
+        let sequence = FrameSequence::parse(&data[..len]).unwrap();
+        let frame = sequence.get(1).unwrap();
+        assert_eq!(frame.level(0, 0), 0);
+        assert_eq!(frame.level(1, 0), GreyscaleFrame::<5, 5>::MAX_LEVEL);
+        assert_eq!(
+            frame.to_frame::<5, 5, 1>(),
+            Frame::from_image("01111:11111:00000:11111:11110")
+        );
+        assert_eq!(sequence.get(0).unwrap().to_frame::<5, 5, 1>(), HEART);
+    }
+
+    #[test]
+    fn test_frame_size() {
+        let frames = [(GreyscaleFrame::<3, 2>::from_image("109:000"), Duration::from_millis(1))];
+        let mut data = [0; 16];
+        let len = encode_sequence(&frames, Depth::Greyscale, &mut data).unwrap();
+        let frame = FrameSequence::parse(&data[..len]).unwrap().get(0).unwrap();
+
+        // LEDs outside the frame are off, LEDs beyond the target size are dropped
+        assert_eq!(frame.to_greyscale::<4, 1>(), GreyscaleFrame::from_image("1090"));
+        assert_eq!(frame.level(5, 5), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut data = [0; 64];
+        let len = encode_sequence(&frames(), Depth::Greyscale, &mut data).unwrap();
+
+        assert_eq!(
+            FrameSequence::parse(&data[..4]),
+            Err(SequenceError::Length {
+                expected: 10,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            FrameSequence::parse(&data[..len - 1]),
+            Err(SequenceError::Length {
+                expected: len,
+                actual: len - 1
+            })
+        );
+        assert_eq!(
+            FrameSequence::parse(&data[..len + 1]),
+            Err(SequenceError::Length {
+                expected: len,
+                actual: len + 1
+            })
+        );
+
+        let mut bad = data;
+        bad[0] = b'X';
+        assert_eq!(FrameSequence::parse(&bad[..len]), Err(SequenceError::Magic));
+        let mut bad = data;
+        bad[4] = 2;
+        assert_eq!(FrameSequence::parse(&bad[..len]), Err(SequenceError::Version(2)));
+        let mut bad = data;
+        bad[7] = 8;
+        assert_eq!(FrameSequence::parse(&bad[..len]), Err(SequenceError::Depth(8)));
+        let mut bad = data;
+        bad[10 + 15 + 2] = 0xa0;
+        assert_eq!(
+            FrameSequence::parse(&bad[..len]),
+            Err(SequenceError::Level { frame: 1 })
+        );
+
+        // A header without frames is not a sequence
+        let mut empty = data;
+        empty[8..10].copy_from_slice(&0u16.to_le_bytes());
+        assert_eq!(FrameSequence::parse(&empty[..10]), Err(SequenceError::Empty));
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let mut data = [0; 64];
+        assert_eq!(
+            encode_sequence(&frames(), Depth::Greyscale, &mut data[..20]),
+            Err(SequenceError::Length {
+                expected: 55,
+                actual: 20
+            })
+        );
+        let frames = [(GreyscaleFrame::<5, 5>::empty(), Duration::from_secs(70))];
+        assert_eq!(
+            encode_sequence(&frames, Depth::Binary, &mut data),
+            Err(SequenceError::Duration { frame: 0 })
+        );
+        assert_eq!(
+            encode_sequence::<5, 5>(&[], Depth::Binary, &mut data),
+            Err(SequenceError::Empty)
+        );
+    }
+}
@@ -1,17 +1,22 @@
 use core::ops::{AddAssign, SubAssign};
-// TODO: Use const generic expressions to derive data size when stabilized
-const BITMAP_WIDTH: usize = 1;
-// Using u8 for each word
-const BITMAP_WORD_SIZE: usize = 8;
+// Using u32 for each word
+const BITMAP_WORD_SIZE: usize = 32;
 
-/// A bitmap with room for 8 bits used by Frame to create a compact frame buffer
+/// Number of words a [`Bitmap`] needs to hold `nbits` bits.
+///
+/// Use this to size frames wider than 32 bits, e.g. `Frame<33, 5, { bitmap_words(33) }>`.
+pub const fn bitmap_words(nbits: usize) -> usize {
+    nbits.div_ceil(BITMAP_WORD_SIZE)
+}
+
+/// A bitmap stored in `WORDS` 32 bit words used by Frame to create a compact frame buffer
 #[derive(Clone, Copy, PartialEq)]
-pub struct Bitmap {
-    data: [u8; BITMAP_WIDTH],
+pub struct Bitmap<const WORDS: usize = 1> {
+    data: [u32; WORDS],
     nbits: usize,
 }
 
-impl core::fmt::Debug for Bitmap {
+impl<const WORDS: usize> core::fmt::Debug for Bitmap<WORDS> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         for i in 0..self.nbits {
             if self.is_set(i) {
@@ -25,42 +30,42 @@ impl core::fmt::Debug for Bitmap {
 }
 
 #[cfg(feature = "defmt")]
-impl defmt::Format for Bitmap {
+impl<const WORDS: usize> defmt::Format for Bitmap<WORDS> {
     fn format(&self, f: defmt::Formatter<'_>) {
-        let mut s: heapless::String<32> = heapless::String::new();
         for i in 0..self.nbits {
             if self.is_set(i) {
-                s.push('1').unwrap();
+                defmt::write!(f, "1");
             } else {
-                s.push('0').unwrap();
+                defmt::write!(f, "0");
             }
         }
-        defmt::write!(f, "{}", s.as_str());
     }
 }
 
-impl Bitmap {
-    /// Create a new bitmap with initial input and number of bits
-    // TODO: Change input to array when const generics...
-    pub const fn new(input: u8, nbits: usize) -> Self {
-        let mut data = [0; BITMAP_WIDTH];
-        //for i in 0..input.len() {
+impl<const WORDS: usize> Bitmap<WORDS> {
+    /// Create a new bitmap with initial input and number of bits.
+    ///
+    /// The input is right-aligned, i.e. the least significant bit of `input` becomes bit `nbits - 1`,
+    /// and is limited to 32 bits. Use [`Bitmap::set`] to initialize wider bitmaps.
+    pub const fn new(input: u32, nbits: usize) -> Self {
+        assert!(nbits <= BITMAP_WORD_SIZE);
+        let mut bitmap = Self::empty(nbits);
         if nbits < BITMAP_WORD_SIZE {
-            data[0] = input << (BITMAP_WORD_SIZE - nbits);
+            bitmap.data[0] = input << (BITMAP_WORD_SIZE - nbits);
         } else {
-            data[0] = input;
+            bitmap.data[0] = input;
         }
-        //}
-        Self { data, nbits }
+        bitmap
     }
 
     /// Create an empty bitmap with nbits bits
     pub const fn empty(nbits: usize) -> Self {
-        Self { data: [0; 1], nbits }
+        assert!(nbits <= WORDS * BITMAP_WORD_SIZE);
+        Self { data: [0; WORDS], nbits }
     }
 
     /// Set bit n in bitmap
-    pub fn set(&mut self, bit: usize) {
+    pub const fn set(&mut self, bit: usize) {
         assert!(bit < self.nbits);
         let idx: usize = bit / BITMAP_WORD_SIZE;
         let p: usize = bit % BITMAP_WORD_SIZE;
@@ -75,7 +80,7 @@ impl Bitmap {
     }
 
     /// Clear bit n in bitmap
-    pub fn clear(&mut self, bit: usize) {
+    pub const fn clear(&mut self, bit: usize) {
         assert!(bit < self.nbits);
         let idx: usize = bit / BITMAP_WORD_SIZE;
         let p: usize = bit % BITMAP_WORD_SIZE;
@@ -83,85 +88,133 @@ impl Bitmap {
     }
 
     /// Check if bit n is set in bitmap
-    pub fn is_set(&self, bit: usize) -> bool {
+    pub const fn is_set(&self, bit: usize) -> bool {
         assert!(bit < self.nbits);
         let idx: usize = bit / BITMAP_WORD_SIZE;
         let p: usize = bit % BITMAP_WORD_SIZE;
         (self.data[idx] & (1 << ((BITMAP_WORD_SIZE - 1) - p))) != 0
     }
 
+    // Word at idx, with zeros outside of the bitmap
+    fn word(&self, idx: usize) -> u32 {
+        if idx < WORDS {
+            self.data[idx]
+        } else {
+            0
+        }
+    }
+
+    // Clear any bits beyond nbits, so they can't be shifted back in
+    const fn mask(&mut self) {
+        let mut i = 0;
+        while i < WORDS {
+            let start = i * BITMAP_WORD_SIZE;
+            if start >= self.nbits {
+                self.data[i] = 0;
+            } else if self.nbits - start < BITMAP_WORD_SIZE {
+                self.data[i] &= !(u32::MAX >> (self.nbits - start));
+            }
+            i += 1;
+        }
+    }
+
     /// Shift left by nbits bits
     pub fn shift_left(&mut self, nbits: usize) {
-        for b in self.data.iter_mut() {
-            *b <<= nbits;
+        let words = nbits / BITMAP_WORD_SIZE;
+        let bits = nbits % BITMAP_WORD_SIZE;
+        for i in 0..WORDS {
+            let hi = self.word(i + words);
+            self.data[i] = if bits == 0 {
+                hi
+            } else {
+                (hi << bits) | (self.word(i + words + 1) >> (BITMAP_WORD_SIZE - bits))
+            };
         }
+        self.mask();
     }
 
     /// Shift right by nbits bits
     pub fn shift_right(&mut self, nbits: usize) {
-        for b in self.data.iter_mut() {
-            *b >>= nbits;
+        let words = nbits / BITMAP_WORD_SIZE;
+        let bits = nbits % BITMAP_WORD_SIZE;
+        for i in (0..WORDS).rev() {
+            let lo = if i >= words { self.data[i - words] } else { 0 };
+            let carry = if i > words { self.data[i - words - 1] } else { 0 };
+            self.data[i] = if bits == 0 {
+                lo
+            } else {
+                (lo >> bits) | (carry << (BITMAP_WORD_SIZE - bits))
+            };
         }
+        self.mask();
     }
 
     /// Logical OR with another bitmap
-    pub fn or(&mut self, other: &Bitmap) {
-        for i in 0..self.data.len() {
+    pub const fn or(&mut self, other: &Bitmap<WORDS>) {
+        let mut i = 0;
+        while i < WORDS {
             self.data[i] |= other.data[i];
+            i += 1;
         }
     }
 
     /// Logical AND with another bitmap
-    pub fn and(&mut self, other: &Bitmap) {
-        for i in 0..self.data.len() {
+    pub const fn and(&mut self, other: &Bitmap<WORDS>) {
+        let mut i = 0;
+        while i < WORDS {
             self.data[i] &= other.data[i];
+            i += 1;
         }
     }
+
+    /// Logical XOR with another bitmap
+    pub const fn xor(&mut self, other: &Bitmap<WORDS>) {
+        let mut i = 0;
+        while i < WORDS {
+            self.data[i] ^= other.data[i];
+            i += 1;
+        }
+    }
+
+    /// Logical NOT of all bits in bitmap
+    pub const fn invert(&mut self) {
+        let mut i = 0;
+        while i < WORDS {
+            self.data[i] = !self.data[i];
+            i += 1;
+        }
+        self.mask();
+    }
 }
 
 /// An NxM frame that can be displayed on a LED matrix.
 ///
-/// NOTE: Currently restricted to 8 bit width
+/// Each row is stored in a [`Bitmap`] of `WORDS` 32 bit words. The default of a single word
+/// supports frames up to 32 bits wide, see [`bitmap_words`] for wider frames.
 #[derive(Clone, Copy, PartialEq)]
-pub struct Frame<const XSIZE: usize, const YSIZE: usize> {
-    bitmap: [Bitmap; YSIZE],
+pub struct Frame<const XSIZE: usize, const YSIZE: usize, const WORDS: usize = 1> {
+    bitmap: [Bitmap<WORDS>; YSIZE],
 }
 
-impl<const XSIZE: usize, const YSIZE: usize> core::fmt::Debug for Frame<XSIZE, YSIZE> {
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> core::fmt::Debug for Frame<XSIZE, YSIZE, WORDS> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        for (i, b) in self.bitmap.iter().enumerate() {
-            for j in 0..b.nbits {
-                if self.bitmap[i].is_set(j) {
-                    write!(f, "1")?;
-                } else {
-                    write!(f, "0")?;
-                }
-            }
-            writeln!(f)?;
+        for b in self.bitmap.iter() {
+            writeln!(f, "{:?}", b)?;
         }
         Ok(())
     }
 }
 
 #[cfg(feature = "defmt")]
-impl<const XSIZE: usize, const YSIZE: usize> defmt::Format for Frame<XSIZE, YSIZE> {
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> defmt::Format for Frame<XSIZE, YSIZE, WORDS> {
     fn format(&self, f: defmt::Formatter<'_>) {
-        let mut s: heapless::String<1056> = heapless::String::new();
-        for (i, b) in self.bitmap.iter().enumerate() {
-            for j in 0..b.nbits {
-                if self.bitmap[i].is_set(j) {
-                    s.push('1').unwrap();
-                } else {
-                    s.push('0').unwrap();
-                }
-            }
-            s.push('\n').unwrap();
+        for b in self.bitmap.iter() {
+            defmt::write!(f, "{}\n", b);
         }
-        defmt::write!(f, "{}", s.as_str());
     }
 }
 
-impl<const XSIZE: usize, const YSIZE: usize> Frame<XSIZE, YSIZE> {
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> Frame<XSIZE, YSIZE, WORDS> {
     /// Create an empty frame
     pub const fn empty() -> Self {
         Self {
@@ -170,7 +223,7 @@ impl<const XSIZE: usize, const YSIZE: usize> Frame<XSIZE, YSIZE> {
     }
 
     /// Create a frame from a bitmap array
-    pub const fn new(bitmap: [Bitmap; YSIZE]) -> Self {
+    pub const fn new(bitmap: [Bitmap<WORDS>; YSIZE]) -> Self {
         Self { bitmap }
     }
 
@@ -182,24 +235,26 @@ impl<const XSIZE: usize, const YSIZE: usize> Frame<XSIZE, YSIZE> {
     }
 
     /// Enable (x, y) on this frame
-    pub fn set(&mut self, x: usize, y: usize) {
+    pub const fn set(&mut self, x: usize, y: usize) {
         self.bitmap[y].set(x);
     }
 
     /// Disable (x, y) on this frame
-    pub fn unset(&mut self, x: usize, y: usize) {
+    pub const fn unset(&mut self, x: usize, y: usize) {
         self.bitmap[y].clear(x);
     }
 
     /// Check if (x, y) is set on this frame
-    pub fn is_set(&self, x: usize, y: usize) -> bool {
+    pub const fn is_set(&self, x: usize, y: usize) -> bool {
         self.bitmap[y].is_set(x)
     }
 
     /// Logical OR with another frame
-    pub fn or(&mut self, other: &Frame<XSIZE, YSIZE>) {
-        for i in 0..self.bitmap.len() {
+    pub const fn or(&mut self, other: &Frame<XSIZE, YSIZE, WORDS>) {
+        let mut i = 0;
+        while i < YSIZE {
             self.bitmap[i].or(&other.bitmap[i]);
+            i += 1;
         }
     }
 
@@ -218,21 +273,162 @@ impl<const XSIZE: usize, const YSIZE: usize> Frame<XSIZE, YSIZE> {
     }
 
     /// Logical AND with another frame
-    pub fn and(&mut self, other: &Frame<XSIZE, YSIZE>) {
-        for i in 0..self.bitmap.len() {
+    pub const fn and(&mut self, other: &Frame<XSIZE, YSIZE, WORDS>) {
+        let mut i = 0;
+        while i < YSIZE {
             self.bitmap[i].and(&other.bitmap[i]);
+            i += 1;
+        }
+    }
+
+    /// Logical XOR with another frame
+    pub const fn xor(&mut self, other: &Frame<XSIZE, YSIZE, WORDS>) {
+        let mut i = 0;
+        while i < YSIZE {
+            self.bitmap[i].xor(&other.bitmap[i]);
+            i += 1;
+        }
+    }
+
+    /// Invert all points on this frame
+    pub const fn invert(&mut self) {
+        let mut i = 0;
+        while i < YSIZE {
+            self.bitmap[i].invert();
+            i += 1;
+        }
+    }
+
+    /// Shift all rows up, clearing the rows at the bottom
+    pub const fn shift_up(&mut self, nrows: usize) {
+        let mut y = 0;
+        while y < YSIZE {
+            self.bitmap[y] = if y + nrows < YSIZE {
+                self.bitmap[y + nrows]
+            } else {
+                Bitmap::empty(XSIZE)
+            };
+            y += 1;
+        }
+    }
+
+    /// Shift all rows down, clearing the rows at the top
+    pub const fn shift_down(&mut self, nrows: usize) {
+        let mut y = YSIZE;
+        while y > 0 {
+            y -= 1;
+            self.bitmap[y] = if y >= nrows {
+                self.bitmap[y - nrows]
+            } else {
+                Bitmap::empty(XSIZE)
+            };
+        }
+    }
+
+    /// Mirror the frame left to right
+    pub const fn flip_horizontal(&mut self) {
+        let mut y = 0;
+        while y < YSIZE {
+            let row = self.bitmap[y];
+            let mut x = 0;
+            while x < XSIZE {
+                if row.is_set(XSIZE - 1 - x) {
+                    self.bitmap[y].set(x);
+                } else {
+                    self.bitmap[y].clear(x);
+                }
+                x += 1;
+            }
+            y += 1;
         }
     }
+
+    /// Mirror the frame top to bottom
+    pub const fn flip_vertical(&mut self) {
+        let mut y = 0;
+        while y < YSIZE / 2 {
+            let row = self.bitmap[y];
+            self.bitmap[y] = self.bitmap[YSIZE - 1 - y];
+            self.bitmap[YSIZE - 1 - y] = row;
+            y += 1;
+        }
+    }
+
+    /// Rotate the frame by 180 degrees
+    pub const fn rotate_180(&mut self) {
+        self.flip_horizontal();
+        self.flip_vertical();
+    }
+
+    /// Return the frame mirrored along its diagonal, swapping rows and columns
+    pub const fn transpose(&self) -> Frame<YSIZE, XSIZE, WORDS> {
+        let mut result = Frame::empty();
+        let mut y = 0;
+        while y < XSIZE {
+            let mut x = 0;
+            while x < YSIZE {
+                if self.is_set(y, x) {
+                    result.set(x, y);
+                }
+                x += 1;
+            }
+            y += 1;
+        }
+        result
+    }
+
+    /// Return the frame rotated clockwise by 90 degrees
+    pub const fn rotate_cw(&self) -> Frame<YSIZE, XSIZE, WORDS> {
+        let mut result = self.transpose();
+        result.flip_horizontal();
+        result
+    }
+
+    /// Return the frame rotated counter-clockwise by 90 degrees
+    pub const fn rotate_ccw(&self) -> Frame<YSIZE, XSIZE, WORDS> {
+        let mut result = self.transpose();
+        result.flip_vertical();
+        result
+    }
 }
 
-impl<const XSIZE: usize, const YSIZE: usize> Default for Frame<XSIZE, YSIZE> {
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> Default for Frame<XSIZE, YSIZE, WORDS> {
     fn default() -> Self {
         Frame::empty()
     }
 }
 
+/// Orientation of a LED matrix, as the clockwise rotation applied to frames when displayed.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Orientation {
+    /// Frames are displayed as is
+    #[default]
+    Rotate0,
+    /// Frames are rotated by 90 degrees
+    Rotate90,
+    /// Frames are rotated by 180 degrees
+    Rotate180,
+    /// Frames are rotated by 270 degrees
+    Rotate270,
+}
+
+impl Orientation {
+    /// Coordinates of the pixel displayed at (x, y) in the unrotated frame.
+    ///
+    /// Rotating by 90 or 270 degrees requires width and height to be equal.
+    pub(crate) const fn source(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Orientation::Rotate0 => (x, y),
+            Orientation::Rotate90 => (y, height - 1 - x),
+            Orientation::Rotate180 => (width - 1 - x, height - 1 - y),
+            Orientation::Rotate270 => (width - 1 - y, x),
+        }
+    }
+}
+
 /// A brightness setting for the display.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Brightness(u8);
 
 impl Brightness {
@@ -242,6 +438,9 @@ impl Brightness {
     /// Lowest brightness
     pub const MIN: Brightness = Brightness(0);
 
+    /// Default brightness
+    pub const DEFAULT: Brightness = Brightness(5);
+
     /// Create a new brightness with a custom level
     pub fn new(level: u8) -> Self {
         Self(level.clamp(Self::MIN.0, Self::MAX.0))
@@ -255,7 +454,7 @@ impl Brightness {
 
 impl Default for Brightness {
     fn default() -> Self {
-        Self(5)
+        Self::DEFAULT
     }
 }
 
@@ -271,7 +470,6 @@ impl SubAssign<u8> for Brightness {
     }
 }
 
-/*
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,14 +494,11 @@ mod tests {
         assert!(b.is_set(3));
         assert!(b.is_set(4));
 
-        /*
-        TODO: When const expressions is allowed
-        let mut b: Bitmap = Bitmap::empty(33);
+        let mut b: Bitmap<2> = Bitmap::empty(33);
         b.set(16);
         b.set(32);
         assert!(b.is_set(16));
         assert!(b.is_set(32));
-        */
 
         let b: Bitmap = Bitmap::new(0b01000, 5);
         assert!(!b.is_set(0));
@@ -334,5 +529,179 @@ mod tests {
         assert!(b.is_set(3));
         assert!(!b.is_set(4));
     }
+
+    #[test]
+    fn test_bitmap_shift_out() {
+        // Bits shifted beyond the bitmap are dropped
+        let mut b: Bitmap = Bitmap::new(0b00011, 5);
+        b.shift_right(2);
+        b.shift_left(2);
+        assert_eq!(b, Bitmap::new(0b00000, 5));
+
+        let mut b: Bitmap = Bitmap::new(0b11111, 5);
+        b.shift_left(5);
+        assert_eq!(b, Bitmap::empty(5));
+    }
+
+    #[test]
+    fn test_bitmap_16() {
+        let mut b: Bitmap = Bitmap::new(0b1000_0000_0000_0001, 16);
+        assert!(b.is_set(0));
+        assert!(b.is_set(15));
+
+        b.shift_right(3);
+        assert!(!b.is_set(0));
+        assert!(b.is_set(3));
+        assert!(!b.is_set(15));
+
+        b.shift_left(1);
+        assert!(b.is_set(2));
+
+        let mut other: Bitmap = Bitmap::new(0b0010_0000_0000_0011, 16);
+        other.or(&b);
+        assert_eq!(other, Bitmap::new(0b0010_0000_0000_0011, 16));
+        other.and(&Bitmap::new(0b0000_0000_0000_0010, 16));
+        assert_eq!(other, Bitmap::new(0b0000_0000_0000_0010, 16));
+    }
+
+    #[test]
+    fn test_bitmap_33() {
+        let mut b: Bitmap<{ bitmap_words(33) }> = Bitmap::empty(33);
+        b.set(30);
+        b.set(31);
+
+        // Carry across the word boundary
+        b.shift_right(2);
+        assert!(!b.is_set(30));
+        assert!(!b.is_set(31));
+        assert!(b.is_set(32));
+        assert!(!b.is_set(0));
+
+        b.shift_left(32);
+        assert!(b.is_set(0));
+        assert!(!b.is_set(32));
+
+        b.shift_right(32);
+        assert!(b.is_set(32));
+        assert!(!b.is_set(0));
+
+        b.shift_left(1);
+        assert!(b.is_set(31));
+        assert!(!b.is_set(32));
+
+        // Bits shifted beyond the last word are dropped
+        b.shift_right(2);
+        b.shift_left(2);
+        assert_eq!(b, Bitmap::empty(33));
+
+        let mut a: Bitmap<2> = Bitmap::empty(33);
+        a.set(0);
+        a.set(32);
+        let mut c: Bitmap<2> = Bitmap::empty(33);
+        c.set(32);
+        c.set(1);
+        a.and(&c);
+        assert!(!a.is_set(0));
+        assert!(!a.is_set(1));
+        assert!(a.is_set(32));
+        a.or(&c);
+        assert!(a.is_set(1));
+    }
+
+    #[rustfmt::skip]
+    const ARROW: [u32; 5] = [
+        0b00100,
+        0b01110,
+        0b10101,
+        0b00100,
+        0b00100,
+    ];
+
+    fn frame(rows: [u32; 5]) -> Frame<5, 5> {
+        Frame::new(rows.map(|r| Bitmap::new(r, 5)))
+    }
+
+    #[test]
+    fn test_shift_up_down() {
+        let mut f = frame(ARROW);
+        f.shift_up(2);
+        assert_eq!(f, frame([0b10101, 0b00100, 0b00100, 0, 0]));
+        f.shift_down(3);
+        assert_eq!(f, frame([0, 0, 0, 0b10101, 0b00100]));
+        f.shift_down(5);
+        assert_eq!(f, Frame::empty());
+    }
+
+    #[test]
+    fn test_flip() {
+        let mut f = frame([0b11000, 0b00100, 0b00000, 0b00010, 0b00001]);
+        f.flip_horizontal();
+        assert_eq!(f, frame([0b00011, 0b00100, 0b00000, 0b01000, 0b10000]));
+        f.flip_vertical();
+        assert_eq!(f, frame([0b10000, 0b01000, 0b00000, 0b00100, 0b00011]));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let up = frame(ARROW);
+        let right = frame([0b00100, 0b00010, 0b11111, 0b00010, 0b00100]);
+        let down = frame([0b00100, 0b00100, 0b10101, 0b01110, 0b00100]);
+        let left = frame([0b00100, 0b01000, 0b11111, 0b01000, 0b00100]);
+
+        assert_eq!(up.rotate_cw(), right);
+        assert_eq!(right.rotate_cw(), down);
+        assert_eq!(up.rotate_ccw(), left);
+        assert_eq!(left.rotate_ccw(), down);
+
+        let mut f = up;
+        f.rotate_180();
+        assert_eq!(f, down);
+
+        assert_eq!(up.transpose(), left);
+        assert_eq!(right.transpose(), down);
+    }
+
+    #[test]
+    fn test_rotate_non_square() {
+        let mut f: Frame<3, 2> = Frame::empty();
+        f.set(0, 0);
+        f.set(2, 1);
+        let r: Frame<2, 3> = f.rotate_cw();
+        assert!(r.is_set(1, 0));
+        assert!(r.is_set(0, 2));
+        assert!(!r.is_set(0, 0));
+        assert_eq!(r.rotate_ccw(), f);
+    }
+
+    #[test]
+    fn test_orientation() {
+        let up = frame(ARROW);
+        for (orientation, rotated) in [
+            (Orientation::Rotate0, up),
+            (Orientation::Rotate90, up.rotate_cw()),
+            (Orientation::Rotate180, up.rotate_cw().rotate_cw()),
+            (Orientation::Rotate270, up.rotate_ccw()),
+        ] {
+            for y in 0..5 {
+                for x in 0..5 {
+                    let (sx, sy) = orientation.source(x, y, 5, 5);
+                    assert_eq!(up.is_set(sx, sy), rotated.is_set(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_frame_33() {
+        let mut frame: Frame<33, 2, { bitmap_words(33) }> = Frame::empty();
+        frame.set(0, 0);
+        frame.set(32, 1);
+        frame.shift_right(32);
+        assert!(frame.is_set(32, 0));
+        assert!(!frame.is_set(0, 0));
+        assert!(!frame.is_set(32, 1));
+
+        frame.shift_left(32);
+        assert!(frame.is_set(0, 0));
+    }
 }
-*/
@@ -0,0 +1,190 @@
+//! Visualization of values such as sensor readings
+//!
+//! Frames can be created showing a value relative to a maximum, and [`Fixed`] formats
+//! fixed-point numbers for [`LedMatrix::show_number`](super::LedMatrix::show_number).
+use core::fmt;
+
+use super::Frame;
+
+/// A fixed-point number, formatted with the given number of decimals.
+///
+/// `Fixed::new(2150, 2)` is formatted as `21.50`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fixed {
+    value: i32,
+    decimals: u8,
+}
+
+impl Fixed {
+    /// Create a number from a value scaled by `10^decimals`. At most 9 decimals are supported.
+    pub const fn new(value: i32, decimals: u8) -> Self {
+        assert!(decimals <= 9);
+        Self { value, decimals }
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = 10u32.pow(self.decimals as u32);
+        let abs = self.value.unsigned_abs();
+        let sign = if self.value < 0 { "-" } else { "" };
+        if self.decimals == 0 {
+            write!(f, "{}{}", sign, abs)
+        } else {
+            let width = self.decimals as usize;
+            write!(f, "{}{}.{:0width$}", sign, abs / scale, abs % scale, width = width)
+        }
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize, const WORDS: usize> Frame<XSIZE, YSIZE, WORDS> {
+    /// Create a bar graph of the value like MakeCode's `plotBarGraph`, growing from the center
+    /// of the bottom row outwards and then upwards. At least one LED is lit.
+    pub fn bar_graph(value: u32, max: u32) -> Self {
+        let center = XSIZE / 2;
+        let steps = YSIZE * (center + 1);
+        let lit = (value.min(max) as u64 * steps as u64 / max.max(1) as u64) as usize;
+
+        let mut frame = Self::empty();
+        for (k, (y, x)) in (0..YSIZE)
+            .rev()
+            .flat_map(|y| (0..=center).map(move |x| (y, x)))
+            .enumerate()
+        {
+            if k <= lit {
+                frame.set(center - x, y);
+                if center + x < XSIZE {
+                    frame.set(center + x, y);
+                }
+            }
+        }
+        frame
+    }
+
+    /// Create a frame lighting a share of all LEDs proportional to the value, in reading order
+    pub fn dot_graph(value: u32, max: u32) -> Self {
+        let total = XSIZE * YSIZE;
+        let lit = (value.min(max) as u64 * total as u64).div_ceil(max.max(1) as u64) as usize;
+
+        let mut frame = Self::empty();
+        for i in 0..lit {
+            frame.set(i % XSIZE, i / XSIZE);
+        }
+        frame
+    }
+
+    /// Create a level meter of the value, lighting rows from the bottom. The center column
+    /// widens to the adjacent columns at medium levels and to the full width at high levels.
+    pub fn level_meter(value: u32, max: u32) -> Self {
+        let rows = (value.min(max) as u64 * YSIZE as u64).div_ceil(max.max(1) as u64) as usize;
+        let center = XSIZE / 2;
+        let half = if 5 * rows >= 4 * YSIZE {
+            center
+        } else if 5 * rows >= 3 * YSIZE {
+            center.min(1)
+        } else {
+            0
+        };
+
+        let mut frame = Self::empty();
+        for y in YSIZE - rows..YSIZE {
+            for x in center - half..=(center + half).min(XSIZE - 1) {
+                frame.set(x, y);
+            }
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::*;
+
+    fn format(value: impl fmt::Display) -> heapless::String<16> {
+        let mut s = heapless::String::new();
+        write!(s, "{}", value).unwrap();
+        s
+    }
+
+    #[test]
+    fn test_fixed() {
+        assert_eq!(format(Fixed::new(2150, 2)), "21.50");
+        assert_eq!(format(Fixed::new(-5, 2)), "-0.05");
+        assert_eq!(format(Fixed::new(-215, 1)), "-21.5");
+        assert_eq!(format(Fixed::new(42, 0)), "42");
+        assert_eq!(format(Fixed::new(i32::MIN, 9)), "-2.147483648");
+    }
+
+    #[test]
+    fn test_bar_graph() {
+        // The center of the bottom row is always lit
+        assert_eq!(
+            Frame::<5, 5>::bar_graph(0, 15),
+            Frame::from_image("00000:00000:00000:00000:00900")
+        );
+        assert_eq!(
+            Frame::<5, 5>::bar_graph(2, 15),
+            Frame::from_image("00000:00000:00000:00000:99999")
+        );
+        assert_eq!(
+            Frame::<5, 5>::bar_graph(4, 15),
+            Frame::from_image("00000:00000:00000:09990:99999")
+        );
+        assert_eq!(
+            Frame::<5, 5>::bar_graph(14, 15),
+            Frame::from_image("99999:99999:99999:99999:99999")
+        );
+        assert_eq!(Frame::<5, 5>::bar_graph(50, 100), Frame::bar_graph(7, 15));
+        assert_eq!(Frame::<5, 5>::bar_graph(500, 100), Frame::bar_graph(15, 15));
+        assert_eq!(Frame::<5, 5>::bar_graph(7, 0), Frame::bar_graph(0, 15));
+
+        // Even widths have one more column left of the center
+        assert_eq!(Frame::<4, 2>::bar_graph(1, 6), Frame::from_image("0000:0111"));
+        assert_eq!(Frame::<4, 2>::bar_graph(2, 6), Frame::from_image("0000:1111"));
+    }
+
+    #[test]
+    fn test_dot_graph() {
+        assert_eq!(Frame::<5, 5>::dot_graph(0, 25), Frame::empty());
+        assert_eq!(
+            Frame::<5, 5>::dot_graph(7, 25),
+            Frame::from_image("11111:11000:00000:00000:00000")
+        );
+        assert_eq!(
+            Frame::<5, 5>::dot_graph(1, 100),
+            Frame::from_image("10000:00000:00000:00000:00000")
+        );
+        assert_eq!(
+            Frame::<5, 5>::dot_graph(100, 100),
+            Frame::from_image("11111:11111:11111:11111:11111")
+        );
+    }
+
+    #[test]
+    fn test_level_meter() {
+        assert_eq!(Frame::<5, 5>::level_meter(0, 255), Frame::empty());
+        assert_eq!(
+            Frame::<5, 5>::level_meter(51, 255),
+            Frame::from_image("00000:00000:00000:00000:00100")
+        );
+        assert_eq!(
+            Frame::<5, 5>::level_meter(52, 255),
+            Frame::from_image("00000:00000:00000:00100:00100")
+        );
+        assert_eq!(
+            Frame::<5, 5>::level_meter(150, 255),
+            Frame::from_image("00000:00000:01110:01110:01110")
+        );
+        assert_eq!(
+            Frame::<5, 5>::level_meter(200, 255),
+            Frame::from_image("00000:11111:11111:11111:11111")
+        );
+        assert_eq!(
+            Frame::<5, 5>::level_meter(255, 255),
+            Frame::from_image("11111:11111:11111:11111:11111")
+        );
+    }
+}
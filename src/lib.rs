@@ -1,5 +1,5 @@
-#![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
 mod board;
@@ -0,0 +1,463 @@
+//! Melodies written as RTTTL ring tones or in MicroPython and MakeCode music notation
+//!
+//! Melodies borrow their text and produce [`Note`]s one at a time, so they can be stored as
+//! string constants and played without any buffers.
+//!
+//! # Examples
+//!
+//! ```
+//! use microbit_bsp::speaker::Melody;
+//!
+//! let melody = Melody::parse("c4:4 e g c5:8").unwrap();
+//! assert_eq!(melody.notes().count(), 4);
+//!
+//! let melody = Melody::parse_rtttl("Beep:d=8,o=5,b=120:c,e,g,4c6").unwrap();
+//! assert_eq!(melody.tempo(), 120);
+//! ```
+use core::fmt;
+
+use super::{NamedPitch, Note, Pitch};
+
+/// Tempo in beats per minute used by the music notation
+pub const DEFAULT_TEMPO: u32 = 120;
+
+/// Highest tempo in beats per minute, keeping the length of every note within a `u32`
+pub const MAX_TEMPO: u32 = 1000;
+
+/// Ticks per beat in the music notation, as in MicroPython
+pub const TICKS_PER_BEAT: u32 = 4;
+
+// Highest octave of the named pitches
+const MAX_OCTAVE: u32 = 8;
+
+// Note lengths allowed in RTTTL, as fractions of a whole note
+const RTTTL_DURATIONS: [u32; 7] = [1, 2, 4, 8, 16, 32, 64];
+
+/// How the notes of a melody are written
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Notation {
+    /// Ring Tone Text Transfer Language, e.g. `"name:d=4,o=5,b=120:8c,e,g"`. Each note has the
+    /// length as a fraction of a whole note, the note, an optional `.` to make it half as long
+    /// again and the octave. Missing lengths and octaves use the defaults in the header.
+    Rtttl,
+    /// MicroPython and MakeCode notation, e.g. `"c4:4 e g"`. Each note has the note with an
+    /// optional `#` or `b`, the octave and the length in ticks after a `:`. A missing octave or
+    /// length is taken from the previous note, starting with octave 4 and 4 ticks. `r` is a rest.
+    Music,
+}
+
+/// Errors produced when parsing melodies
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MelodyError {
+    /// The RTTTL header with the name and the defaults is malformed
+    Header,
+    /// The tempo is 0 or above [`MAX_TEMPO`]
+    Tempo,
+    /// A note could not be parsed
+    Note {
+        /// Index of the note in the melody
+        index: usize,
+    },
+}
+
+impl fmt::Display for MelodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MelodyError::Header => write!(f, "invalid header"),
+            MelodyError::Tempo => write!(f, "invalid tempo"),
+            MelodyError::Note { index } => write!(f, "invalid note {}", index),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Defaults {
+    octave: u32,
+    duration: u32,
+}
+
+/// A melody borrowing its notes from a string
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Melody<'a> {
+    notes: &'a str,
+    notation: Notation,
+    tempo: u32,
+    defaults: Defaults,
+}
+
+impl<'a> Melody<'a> {
+    /// Parse a melody in MicroPython and MakeCode notation, e.g. `"c4:4 e g"`, played at
+    /// [`DEFAULT_TEMPO`]
+    pub fn parse(notes: &'a str) -> Result<Self, MelodyError> {
+        Self::validate(Self {
+            notes,
+            notation: Notation::Music,
+            tempo: DEFAULT_TEMPO,
+            defaults: Defaults { octave: 4, duration: 4 },
+        })
+    }
+
+    /// Parse an RTTTL ring tone, e.g. `"name:d=4,o=5,b=120:8c,e,g"`. Defaults missing in the
+    /// header are a quarter note, octave 6 and 63 beats per minute.
+    pub fn parse_rtttl(rtttl: &'a str) -> Result<Self, MelodyError> {
+        let mut parts = rtttl.splitn(3, ':');
+        let (Some(_name), Some(header), Some(notes)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(MelodyError::Header);
+        };
+
+        let mut defaults = Defaults { octave: 6, duration: 4 };
+        let mut tempo = 63;
+        for setting in header.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = setting.split_once('=').ok_or(MelodyError::Header)?;
+            let value: u32 = value.trim().parse().map_err(|_| MelodyError::Header)?;
+            match key.trim() {
+                "d" if RTTTL_DURATIONS.contains(&value) => defaults.duration = value,
+                "o" if value <= MAX_OCTAVE => defaults.octave = value,
+                "b" => tempo = value,
+                _ => return Err(MelodyError::Header),
+            }
+        }
+
+        Self::validate(Self {
+            notes,
+            notation: Notation::Rtttl,
+            tempo,
+            defaults,
+        })
+    }
+
+    // Check that all notes can be parsed, so iterating never fails
+    fn validate(self) -> Result<Self, MelodyError> {
+        if self.tempo == 0 || self.tempo > MAX_TEMPO {
+            return Err(MelodyError::Tempo);
+        }
+        let mut notes = self.notes();
+        while let Some(note) = notes.parse_next() {
+            note?;
+        }
+        Ok(self)
+    }
+
+    /// How the notes are written
+    pub fn notation(&self) -> Notation {
+        self.notation
+    }
+
+    /// Tempo in beats per minute
+    pub fn tempo(&self) -> u32 {
+        self.tempo
+    }
+
+    /// Return the melody played at a different tempo in beats per minute, limited to
+    /// 1..=[`MAX_TEMPO`]
+    pub fn with_tempo(self, tempo: u32) -> Self {
+        Self {
+            tempo: tempo.clamp(1, MAX_TEMPO),
+            ..self
+        }
+    }
+
+    /// Iterate over the notes of the melody
+    pub fn notes(&self) -> Notes<'a> {
+        Notes {
+            rest: self.notes,
+            notation: self.notation,
+            tempo: self.tempo,
+            defaults: self.defaults,
+            index: 0,
+        }
+    }
+
+    /// Total duration of the melody in milliseconds, saturating at `u32::MAX`
+    pub fn duration_ms(&self) -> u32 {
+        self.notes().fold(0, |total, Note(_, ms)| total.saturating_add(ms))
+    }
+}
+
+/// Iterator over the notes of a [`Melody`]
+pub struct Notes<'a> {
+    rest: &'a str,
+    notation: Notation,
+    tempo: u32,
+    defaults: Defaults,
+    index: usize,
+}
+
+impl Notes<'_> {
+    fn parse_next(&mut self) -> Option<Result<Note, MelodyError>> {
+        let is_separator = |c: char| c.is_whitespace() || c == ',';
+        let text = self.rest.trim_start_matches(is_separator);
+        if text.is_empty() {
+            self.rest = text;
+            return None;
+        }
+        let (token, rest) = text.split_at(text.find(is_separator).unwrap_or(text.len()));
+        self.rest = rest;
+
+        let index = self.index;
+        self.index += 1;
+        let note = match self.notation {
+            Notation::Rtttl => rtttl_note(token, &self.defaults, self.tempo),
+            Notation::Music => music_note(token, &mut self.defaults, self.tempo),
+        };
+        Some(note.ok_or(MelodyError::Note { index }))
+    }
+}
+
+impl Iterator for Notes<'_> {
+    type Item = Note;
+
+    fn next(&mut self) -> Option<Note> {
+        // Melodies are validated when parsed
+        self.parse_next().and_then(Result::ok)
+    }
+}
+
+// Cursor over the characters of a single note
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(token: &'a str) -> Self {
+        Self {
+            bytes: token.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let c = self.bytes.get(self.pos)?.to_ascii_lowercase();
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let found = self.bytes.get(self.pos).map(u8::to_ascii_lowercase) == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        core::str::from_utf8(&self.bytes[start..self.pos]).ok()?.parse().ok()
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+}
+
+// Semitone of the note name within an octave
+fn semitone(name: u8) -> Option<i32> {
+    match name {
+        b'c' => Some(0),
+        b'd' => Some(2),
+        b'e' => Some(4),
+        b'f' => Some(5),
+        b'g' => Some(7),
+        b'a' => Some(9),
+        b'b' => Some(11),
+        _ => None,
+    }
+}
+
+// Pitch of the note, which may be shifted into the neighbouring octaves by `#` or `b`
+fn pitch(octave: u32, semitone: i32) -> Option<Pitch> {
+    if octave > MAX_OCTAVE {
+        return None;
+    }
+    let note = u32::try_from(octave as i32 * 12 + semitone).ok()?;
+    NamedPitch::from_note(note / 12, note % 12).map(Pitch::Named)
+}
+
+fn rtttl_note(token: &str, defaults: &Defaults, tempo: u32) -> Option<Note> {
+    let mut cursor = Cursor::new(token);
+    let duration = match cursor.number() {
+        Some(d) if RTTTL_DURATIONS.contains(&d) => d,
+        Some(_) => return None,
+        None => defaults.duration,
+    };
+    let name = cursor.next()?;
+    let sharp = cursor.eat(b'#') as i32;
+    let mut dotted = cursor.eat(b'.');
+    let octave = cursor.number().unwrap_or(defaults.octave);
+    dotted |= cursor.eat(b'.');
+    if !cursor.is_done() {
+        return None;
+    }
+
+    let pitch = match name {
+        b'p' => Pitch::Silent,
+        name => pitch(octave, semitone(name)? + sharp)?,
+    };
+    let mut ms = 240_000 / (tempo * duration);
+    if dotted {
+        ms += ms / 2;
+    }
+    Some(Note(pitch, ms))
+}
+
+fn music_note(token: &str, defaults: &mut Defaults, tempo: u32) -> Option<Note> {
+    let mut cursor = Cursor::new(token);
+    let name = cursor.next()?;
+    let accidental = if cursor.eat(b'#') {
+        1
+    } else if cursor.eat(b'b') {
+        -1
+    } else {
+        0
+    };
+    let octave = cursor.number().unwrap_or(defaults.octave);
+    let duration = if cursor.eat(b':') {
+        cursor.number()?
+    } else {
+        defaults.duration
+    };
+    if !cursor.is_done() || octave > MAX_OCTAVE {
+        return None;
+    }
+
+    let pitch = match name {
+        b'r' => Pitch::Silent,
+        name => pitch(octave, semitone(name)? + accidental)?,
+    };
+    // Lengths in ticks are not limited, so notes saturate at `u32::MAX` milliseconds whatever the
+    // tempo the melody is played at
+    let ms = u64::from(duration) * 60_000 / u64::from(tempo * TICKS_PER_BEAT);
+    *defaults = Defaults { octave, duration };
+    Some(Note(pitch, u32::try_from(ms).unwrap_or(u32::MAX)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Frequencies and durations of the notes, with 0 Hz for rests
+    fn notes<const N: usize>(melody: &Melody<'_>) -> [(u32, u32); N] {
+        let mut notes = melody.notes().map(|Note(pitch, ms)| match pitch {
+            Pitch::Named(n) => (n.into_frequency(), ms),
+            Pitch::Silent => (0, ms),
+            Pitch::Frequency(hz) => (hz, ms),
+        });
+        let result = core::array::from_fn(|_| notes.next().unwrap());
+        assert!(notes.next().is_none());
+        result
+    }
+
+    fn note(hz: u32, ms: u32) -> (u32, u32) {
+        (hz, ms)
+    }
+
+    #[test]
+    fn test_music() {
+        let melody = Melody::parse("c4:4 e g").unwrap();
+        assert_eq!(melody.notation(), Notation::Music);
+        assert_eq!(notes(&melody), [note(261, 500), note(329, 500), note(392, 500)]);
+        assert_eq!(melody.duration_ms(), 1500);
+
+        // Octaves and lengths carry over to the following notes
+        let melody = Melody::parse("C4:2 d5\n e:8  r a3").unwrap();
+        assert_eq!(
+            notes(&melody),
+            [
+                note(261, 250),
+                note(587, 250),
+                note(659, 1000),
+                note(0, 1000),
+                note(220, 1000)
+            ]
+        );
+
+        // Sharps and flats may change the octave
+        let melody = Melody::parse("c#4 eb b#4 cb").unwrap();
+        assert_eq!(
+            notes(&melody),
+            [note(277, 500), note(311, 500), note(523, 500), note(246, 500)]
+        );
+
+        assert_eq!(Melody::parse("").unwrap().notes().count(), 0);
+    }
+
+    #[test]
+    fn test_tempo() {
+        let melody = Melody::parse("c4:4 e:2").unwrap().with_tempo(240);
+        assert_eq!(melody.tempo(), 240);
+        assert_eq!(notes(&melody), [note(261, 250), note(329, 125)]);
+
+        // Tempos are limited to the supported range
+        assert_eq!(melody.with_tempo(0).tempo(), 1);
+        let melody = melody.with_tempo(u32::MAX);
+        assert_eq!(melody.tempo(), MAX_TEMPO);
+        assert_eq!(notes(&melody), [note(261, 60), note(329, 30)]);
+    }
+
+    #[test]
+    fn test_rtttl() {
+        let melody = Melody::parse_rtttl("Simpsons:d=4,o=5,b=160:c.6,e6,f#6,8a6,g.6,8a,8f#,2g,p").unwrap();
+        assert_eq!(melody.notation(), Notation::Rtttl);
+        assert_eq!(melody.tempo(), 160);
+        assert_eq!(
+            notes(&melody),
+            [
+                note(1046, 562),
+                note(1318, 375),
+                note(1479, 375),
+                note(1760, 187),
+                note(1567, 562),
+                note(880, 187),
+                note(739, 187),
+                note(783, 750),
+                note(0, 375),
+            ]
+        );
+
+        // Defaults do not carry over between notes
+        let melody = Melody::parse_rtttl("x: d = 8, o = 4, b = 120 :16c5, c, 4c. ").unwrap();
+        assert_eq!(notes(&melody), [note(523, 125), note(261, 250), note(261, 750)]);
+
+        // Missing defaults
+        let melody = Melody::parse_rtttl("x::c").unwrap();
+        assert_eq!(melody.tempo(), 63);
+        assert_eq!(notes(&melody), [note(1046, 952)]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Melody::parse("c4:4 x"), Err(MelodyError::Note { index: 1 }));
+        assert_eq!(Melody::parse("c9"), Err(MelodyError::Note { index: 0 }));
+        assert_eq!(Melody::parse("c b8 b#8"), Err(MelodyError::Note { index: 2 }));
+        assert_eq!(Melody::parse("c4:"), Err(MelodyError::Note { index: 0 }));
+        assert_eq!(Melody::parse("c4:4x"), Err(MelodyError::Note { index: 0 }));
+        assert_eq!(Melody::parse("c4294967295"), Err(MelodyError::Note { index: 0 }));
+
+        // Long notes saturate instead of overflowing
+        let melody = Melody::parse("c4:286331 r c:4294967295").unwrap().with_tempo(1);
+        assert_eq!(
+            notes(&melody),
+            [note(261, 4_294_965_000), note(0, 4_294_965_000), note(261, u32::MAX)]
+        );
+        assert_eq!(melody.duration_ms(), u32::MAX);
+
+        assert_eq!(Melody::parse_rtttl("c,d"), Err(MelodyError::Header));
+        assert_eq!(Melody::parse_rtttl("x:d=3:c"), Err(MelodyError::Header));
+        assert_eq!(Melody::parse_rtttl("x:o=9:c"), Err(MelodyError::Header));
+        assert_eq!(Melody::parse_rtttl("x:q=1:c"), Err(MelodyError::Header));
+        assert_eq!(Melody::parse_rtttl("x:b=0:c"), Err(MelodyError::Tempo));
+        assert_eq!(Melody::parse_rtttl("x:b=4294967295:64c"), Err(MelodyError::Tempo));
+        assert_eq!(
+            Melody::parse_rtttl("x::c4294967295"),
+            Err(MelodyError::Note { index: 0 })
+        );
+        assert_eq!(Melody::parse_rtttl("x::c,3c"), Err(MelodyError::Note { index: 1 }));
+        assert_eq!(Melody::parse_rtttl("x::r"), Err(MelodyError::Note { index: 0 }));
+    }
+}
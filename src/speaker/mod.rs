@@ -0,0 +1,105 @@
+//! Simple speaker utilities for PWM-based synth
+use embassy_nrf::pwm;
+use embassy_time::{Delay, Timer};
+use embedded_hal::delay::DelayNs;
+
+mod melody;
+mod pcm;
+mod pitch;
+mod player;
+mod playlist;
+mod sequencer;
+mod synth;
+mod tone;
+
+pub use melody::*;
+pub use pcm::*;
+pub use pitch::*;
+pub use player::*;
+pub use playlist::*;
+pub use sequencer::*;
+pub use synth::*;
+pub use tone::*;
+
+/// PWM based speaker capable of playing notes with a given pitch
+pub struct PwmSpeaker<'a> {
+    pwm: pwm::SimplePwm<'a>,
+}
+
+impl<'a> PwmSpeaker<'a> {
+    /// Create a new speaker instance
+    pub fn new(pwm: pwm::SimplePwm<'a>) -> Self {
+        Self { pwm }
+    }
+
+    fn start_play(&mut self, frequency: u32) {
+        self.pwm.set_prescaler(pwm::Prescaler::Div4);
+        self.pwm.set_period(frequency);
+        self.pwm.enable();
+        self.pwm.set_duty(0, pwm::DutyCycle::normal(self.pwm.max_duty() / 2));
+    }
+
+    fn stop_play(&mut self) {
+        self.pwm.disable();
+    }
+
+    /// Play a note
+    pub async fn play(&mut self, note: &Note) {
+        let Note(pitch, duration) = note;
+
+        let frequency = match pitch {
+            Pitch::Silent => {
+                Timer::after_millis(u64::from(*duration)).await;
+                return;
+            }
+            Pitch::Named(n) => n.into_frequency(),
+            Pitch::Frequency(f) => *f,
+        };
+
+        self.start_play(frequency);
+        Timer::after_millis(u64::from(*duration)).await;
+        self.stop_play();
+    }
+
+    /// Play a note, blocking variant.
+    pub fn play_blocking(&mut self, note: &Note) {
+        let Note(pitch, duration) = note;
+        let mut delay = Delay;
+
+        let frequency = match pitch {
+            Pitch::Silent => {
+                delay.delay_ms(*duration);
+                return;
+            }
+            Pitch::Named(n) => n.into_frequency(),
+            Pitch::Frequency(f) => *f,
+        };
+
+        self.start_play(frequency);
+        delay.delay_ms(*duration);
+        self.stop_play();
+    }
+
+    /// Play all notes of the melody at its tempo. Use [`Melody::with_tempo`] to play it faster
+    /// or slower.
+    pub async fn play_melody(&mut self, melody: &Melody<'_>) {
+        for note in melody.notes() {
+            self.play(&note).await;
+        }
+    }
+
+    /// Start playing a note in a non-blocking way
+    pub fn start_note(&mut self, pitch: Pitch) {
+        let frequency = match pitch {
+            Pitch::Silent => return,
+            Pitch::Named(n) => n.into_frequency(),
+            Pitch::Frequency(f) => f,
+        };
+        self.start_play(frequency);
+    }
+
+    /// Stop playing a note
+    pub fn stop(&mut self) {
+        self.stop_play();
+    }
+}
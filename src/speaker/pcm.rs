@@ -0,0 +1,505 @@
+//! PCM audio clips played through the PWM as a DAC
+//!
+//! Each sample sets the duty cycle of a PWM carrier well above the audible range, and is held
+//! for a whole number of carrier periods to match the sample rate. Clips borrow their samples,
+//! so they can be kept in flash with `include_bytes!` and converted in small chunks during
+//! playback, see [`PcmSpeaker`](super::PcmSpeaker).
+//!
+//! # Examples
+//!
+//! ```
+//! use microbit_bsp::speaker::{PcmClip, Samples};
+//!
+//! // A 16 kHz WAV file with 8 bit mono samples
+//! # let wav = *b"RIFF\x2a\0\0\0WAVEfmt \x10\0\0\0\x01\0\x01\0\x80\x3e\0\0\x80\x3e\0\0\x01\0\x08\0data\x02\0\0\0\x80\xff";
+//! let clip = PcmClip::parse_wav(&wav).unwrap();
+//! assert_eq!(clip.rate(), 16_000);
+//! assert_eq!(clip.len(), 2);
+//!
+//! let tone = [0i16, 16_000, 0, -16_000];
+//! let clip = PcmClip::new(Samples::I16(&tone), 8_000).unwrap();
+//! ```
+use core::fmt;
+
+/// Clock of the PWM counter, using the undivided 16 MHz clock
+pub const PCM_CLOCK_HZ: u32 = 16_000_000;
+
+/// Lowest supported sample rate
+pub const MIN_PCM_RATE: u32 = 1_000;
+
+/// Highest supported sample rate, with a carrier period of 256 ticks
+pub const MAX_PCM_RATE: u32 = PCM_CLOCK_HZ / 256;
+
+/// Number of samples in each of the two buffers read by the DMA during PCM playback
+pub const PCM_CHUNK: usize = 256;
+
+/// Errors produced when creating clips
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PcmError {
+    /// The sample rate is outside [`MIN_PCM_RATE`] to [`MAX_PCM_RATE`]
+    SampleRate(u32),
+    /// The data is not a RIFF WAVE file
+    Wav,
+    /// The format chunk of the WAV file is missing or the samples are not PCM encoded
+    Format,
+    /// Only mono WAV files are supported
+    Channels(u16),
+    /// Only 8 and 16 bit WAV files are supported
+    BitsPerSample(u16),
+    /// The WAV file has no data chunk
+    Data,
+}
+
+impl fmt::Display for PcmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PcmError::SampleRate(rate) => write!(f, "unsupported sample rate {} Hz", rate),
+            PcmError::Wav => write!(f, "not a WAV file"),
+            PcmError::Format => write!(f, "invalid format"),
+            PcmError::Channels(channels) => write!(f, "unsupported number of channels {}", channels),
+            PcmError::BitsPerSample(bits) => write!(f, "unsupported bits per sample {}", bits),
+            PcmError::Data => write!(f, "missing data"),
+        }
+    }
+}
+
+/// Samples of a clip
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Samples<'a> {
+    /// Unsigned 8 bit samples, with silence at 128
+    U8(&'a [u8]),
+    /// Signed 16 bit samples
+    I16(&'a [i16]),
+    /// Signed 16 bit samples stored as little endian bytes, as in WAV files
+    I16Le(&'a [u8]),
+}
+
+impl Samples<'_> {
+    /// Number of samples
+    pub fn len(&self) -> usize {
+        match self {
+            Samples::U8(s) => s.len(),
+            Samples::I16(s) => s.len(),
+            Samples::I16Le(s) => s.len() / 2,
+        }
+    }
+
+    /// Check if there are no samples
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Sample as an unsigned 16 bit value, with silence at 0x8000
+    fn get(&self, index: usize) -> u16 {
+        match self {
+            Samples::U8(s) => u16::from(s[index]) << 8,
+            Samples::I16(s) => (s[index] as u16) ^ 0x8000,
+            Samples::I16Le(s) => u16::from_le_bytes([s[2 * index], s[2 * index + 1]]) ^ 0x8000,
+        }
+    }
+}
+
+/// PWM settings playing samples at a sample rate
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PcmTiming {
+    rate: u32,
+    top: u16,
+    refresh: u32,
+}
+
+impl PcmTiming {
+    /// Find the carrier for the sample rate. The carrier period is at least 256 ticks of
+    /// [`PCM_CLOCK_HZ`] for 8 bit resolution, and as short as possible above that.
+    pub fn new(rate: u32) -> Result<Self, PcmError> {
+        if !(MIN_PCM_RATE..=MAX_PCM_RATE).contains(&rate) {
+            return Err(PcmError::SampleRate(rate));
+        }
+        let periods = PCM_CLOCK_HZ / (rate * 256);
+        let top = (PCM_CLOCK_HZ + rate * periods / 2) / (rate * periods);
+        Ok(Self {
+            rate,
+            top: top as u16,
+            refresh: periods - 1,
+        })
+    }
+
+    /// The sample rate
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// Counter top setting the carrier period, and the duty cycle of the loudest sample
+    pub fn counter_top(&self) -> u16 {
+        self.top
+    }
+
+    /// Number of additional carrier periods every sample is held
+    pub fn refresh(&self) -> u32 {
+        self.refresh
+    }
+
+    /// Playing time of the number of samples in microseconds
+    pub fn duration_us(&self, samples: usize) -> u64 {
+        let ticks = samples as u64 * u64::from(self.top) * (u64::from(self.refresh) + 1);
+        ticks * 1_000_000 / u64::from(PCM_CLOCK_HZ)
+    }
+
+    // Duty cycle for a sample as an unsigned 16 bit value
+    pub(crate) fn duty(&self, sample: u16) -> u16 {
+        (u32::from(sample) * u32::from(self.top) / 0x10000) as u16
+    }
+}
+
+/// Mono PCM samples with a sample rate
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PcmClip<'a> {
+    samples: Samples<'a>,
+    timing: PcmTiming,
+}
+
+impl<'a> PcmClip<'a> {
+    /// Create a clip playing the samples at the sample rate in Hz
+    pub fn new(samples: Samples<'a>, rate: u32) -> Result<Self, PcmError> {
+        Ok(Self {
+            samples,
+            timing: PcmTiming::new(rate)?,
+        })
+    }
+
+    /// Create a clip from a WAV file with 8 or 16 bit mono PCM samples
+    pub fn parse_wav(wav: &'a [u8]) -> Result<Self, PcmError> {
+        if wav.len() < 12 || &wav[..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+            return Err(PcmError::Wav);
+        }
+
+        let mut format = None;
+        let mut rest = &wav[12..];
+        while rest.len() >= 8 {
+            let id = &rest[..4];
+            let size = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+            // The size of the data is not known when streaming, so it may exceed the file
+            let body = &rest[8..8 + size.min(rest.len() - 8)];
+            match id {
+                b"fmt " => format = Some(parse_format(body)?),
+                b"data" => {
+                    let (bits, rate) = format.ok_or(PcmError::Format)?;
+                    let samples = match bits {
+                        8 => Samples::U8(body),
+                        _ => Samples::I16Le(body),
+                    };
+                    return Self::new(samples, rate);
+                }
+                _ => {}
+            }
+            // Chunks are padded to an even size
+            let next = size.saturating_add(8 + size % 2).min(rest.len());
+            rest = &rest[next..];
+        }
+        Err(PcmError::Data)
+    }
+
+    /// The samples
+    pub fn samples(&self) -> &Samples<'a> {
+        &self.samples
+    }
+
+    /// Number of samples
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Check if the clip has no samples
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The sample rate
+    pub fn rate(&self) -> u32 {
+        self.timing.rate
+    }
+
+    /// PWM settings playing the clip
+    pub fn timing(&self) -> &PcmTiming {
+        &self.timing
+    }
+
+    /// Playing time in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        (self.timing.duration_us(self.len()) / 1000) as u32
+    }
+
+    /// Convert the samples starting at the offset to PWM duty cycles, filling as much of the
+    /// buffer as possible. Returns the number of samples converted.
+    pub fn render(&self, offset: usize, duty: &mut [u16]) -> usize {
+        let count = duty.len().min(self.len().saturating_sub(offset));
+        for (i, d) in duty[..count].iter_mut().enumerate() {
+            *d = self.timing.duty(self.samples.get(offset + i));
+        }
+        count
+    }
+}
+
+// The two buffers of duty cycles played one after the other by the two sequences of the PWM,
+// which loops over both. Sequence `n` plays the buffer `n % 2`, and the buffer of a sequence is
+// refilled with the following chunk once the sequence has ended, while the other one plays.
+pub(crate) struct PcmBuffers {
+    buffers: [[u16; PCM_CHUNK]; 2],
+    silence: u16,
+    // Number of sequences filled, and the first sequence after the last sample
+    queued: u32,
+    end: Option<u32>,
+}
+
+impl PcmBuffers {
+    pub(crate) const fn new() -> Self {
+        Self {
+            buffers: [[0; PCM_CHUNK]; 2],
+            silence: 0,
+            queued: 0,
+            end: None,
+        }
+    }
+
+    // Fill the buffers of the first two sequences, returning false if there is nothing to play
+    pub(crate) fn start(&mut self, timing: &PcmTiming, render: &mut impl FnMut(&mut [u16]) -> usize) -> bool {
+        self.silence = timing.duty(0x8000);
+        self.queued = 0;
+        self.end = None;
+        self.fill(render);
+        self.fill(render);
+        self.end != Some(0)
+    }
+
+    // Refill the buffer of the last sequence ended, while the sequence `ended` plays the other
+    // one. Returns true once all samples have been played.
+    //
+    // When called late, sequences that started before their buffer was refilled play it again.
+    pub(crate) fn refill(&mut self, ended: u32, render: &mut impl FnMut(&mut [u16]) -> usize) -> bool {
+        if self.end.is_some_and(|end| ended >= end) {
+            return true;
+        }
+        if self.queued <= ended + 1 {
+            self.queued = ended + 1;
+            self.fill(render);
+        }
+        false
+    }
+
+    pub(crate) fn buffers(&self) -> &[[u16; PCM_CHUNK]; 2] {
+        &self.buffers
+    }
+
+    // Fill the buffer of the next sequence, padded with silence after the last sample
+    fn fill(&mut self, render: &mut impl FnMut(&mut [u16]) -> usize) {
+        let sequence = self.queued;
+        let buffer = &mut self.buffers[sequence as usize % 2];
+        let count = if self.end.is_some() { 0 } else { render(&mut buffer[..]) };
+        buffer[count..].fill(self.silence);
+        if count < PCM_CHUNK && self.end.is_none() {
+            self.end = Some(if count == 0 { sequence } else { sequence + 1 });
+        }
+        self.queued += 1;
+    }
+}
+
+// Bits per sample and sample rate from the format chunk
+fn parse_format(body: &[u8]) -> Result<(u16, u32), PcmError> {
+    if body.len() < 16 {
+        return Err(PcmError::Format);
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+    // Only uncompressed PCM
+    if u16_at(0) != 1 {
+        return Err(PcmError::Format);
+    }
+    let channels = u16_at(2);
+    if channels != 1 {
+        return Err(PcmError::Channels(channels));
+    }
+    let bits = u16_at(14);
+    if bits != 8 && bits != 16 {
+        return Err(PcmError::BitsPerSample(bits));
+    }
+    Ok((bits, u32::from_le_bytes([body[4], body[5], body[6], body[7]])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A mono WAV file with an extra chunk before the data
+    fn wav<const N: usize>(rate: u32, bits: u16, data: &[u8]) -> heapless::Vec<u8, N> {
+        let mut wav = heapless::Vec::new();
+        let mut push = |bytes: &[u8]| wav.extend_from_slice(bytes).unwrap();
+        push(b"RIFF");
+        push(&(36 + 10 + 8 + data.len() as u32).to_le_bytes());
+        push(b"WAVEfmt ");
+        push(&16u32.to_le_bytes());
+        push(&1u16.to_le_bytes());
+        push(&1u16.to_le_bytes());
+        push(&rate.to_le_bytes());
+        push(&(rate * u32::from(bits) / 8).to_le_bytes());
+        push(&(bits / 8).to_le_bytes());
+        push(&bits.to_le_bytes());
+        push(b"LIST");
+        push(&1u32.to_le_bytes());
+        push(&[0, 0]);
+        push(b"data");
+        push(&(data.len() as u32).to_le_bytes());
+        push(data);
+        wav
+    }
+
+    #[test]
+    fn test_timing() {
+        // Sample rates are matched within 0.1%
+        let timing = PcmTiming::new(8_000).unwrap();
+        assert_eq!((timing.counter_top(), timing.refresh()), (286, 6));
+        assert_eq!(timing.duration_us(8_000), 1_001_000);
+        let timing = PcmTiming::new(16_000).unwrap();
+        assert_eq!((timing.counter_top(), timing.refresh()), (333, 2));
+        assert_eq!(timing.duration_us(16_000), 999_000);
+
+        let timing = PcmTiming::new(MAX_PCM_RATE).unwrap();
+        assert_eq!((timing.counter_top(), timing.refresh()), (256, 0));
+        assert_eq!(PcmTiming::new(70_000), Err(PcmError::SampleRate(70_000)));
+        assert_eq!(PcmTiming::new(0), Err(PcmError::SampleRate(0)));
+        for rate in [MIN_PCM_RATE, 11_025, 22_050, 44_100] {
+            let timing = PcmTiming::new(rate).unwrap();
+            assert!((256..512).contains(&timing.counter_top()));
+            assert!(timing.duration_us(rate as usize).abs_diff(1_000_000) < 2_000);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let samples = [0u8, 128, 255];
+        let clip = PcmClip::new(Samples::U8(&samples), 16_000).unwrap();
+        let mut duty = [0; 2];
+        assert_eq!(clip.render(0, &mut duty), 2);
+        assert_eq!(duty, [0, 166]);
+        assert_eq!(clip.render(2, &mut duty), 1);
+        assert_eq!(duty[0], 331);
+        assert_eq!(clip.render(3, &mut duty), 0);
+
+        let samples = [i16::MIN, 0, i16::MAX];
+        let clip = PcmClip::new(Samples::I16(&samples), 16_000).unwrap();
+        let mut duty = [0; 4];
+        assert_eq!(clip.render(0, &mut duty), 3);
+        assert_eq!(duty[..3], [0, 166, 332]);
+
+        let samples = [0x00, 0x80, 0x00, 0x00, 0xff, 0x7f, 0xff];
+        let clip = PcmClip::new(Samples::I16Le(&samples), 16_000).unwrap();
+        assert_eq!(clip.len(), 3);
+        assert_eq!(clip.render(0, &mut duty), 3);
+        assert_eq!(duty[..3], [0, 166, 332]);
+    }
+
+    // Play the sequences like the PWM looping over both buffers, refilling the buffer of every
+    // sequence once it has ended
+    fn play<const N: usize>(samples: u16) -> heapless::Vec<u16, N> {
+        let timing = PcmTiming::new(16_000).unwrap();
+        let mut samples = 0..samples;
+        let mut render = |duty: &mut [u16]| duty.iter_mut().zip(samples.by_ref()).map(|(d, s)| *d = s).count();
+        let mut buffers = PcmBuffers::new();
+        let mut played = heapless::Vec::new();
+        if !buffers.start(&timing, &mut render) {
+            return played;
+        }
+        for ended in 1.. {
+            played
+                .extend_from_slice(&buffers.buffers()[(ended as usize - 1) % 2])
+                .unwrap();
+
+            // The buffer of the sequence starting now is left alone, also when woken up early
+            let playing = buffers.buffers()[ended as usize % 2];
+            let done = buffers.refill(ended, &mut render);
+            assert_eq!(buffers.refill(ended, &mut render), done);
+            assert_eq!(buffers.buffers()[ended as usize % 2], playing);
+            if done {
+                break;
+            }
+        }
+        played
+    }
+
+    #[test]
+    fn test_double_buffering() {
+        let silence = PcmTiming::new(16_000).unwrap().duty(0x8000);
+
+        // Chunks follow each other without a gap, and the last one is padded with silence
+        let played: heapless::Vec<u16, 1024> = play(600);
+        assert_eq!(played.len(), 3 * PCM_CHUNK);
+        assert!(played[..600].iter().copied().eq(0..600));
+        assert!(played[600..].iter().all(|&d| d == silence));
+
+        let played: heapless::Vec<u16, 1024> = play(2 * PCM_CHUNK as u16);
+        assert!(played.iter().copied().eq(0..2 * PCM_CHUNK as u16));
+        let played: heapless::Vec<u16, 1024> = play(10);
+        assert_eq!(played.len(), PCM_CHUNK);
+        assert!(play::<1024>(0).is_empty());
+    }
+
+    #[test]
+    fn test_refill_late() {
+        let timing = PcmTiming::new(16_000).unwrap();
+        let mut chunks = 0;
+        let mut render = |duty: &mut [u16]| {
+            chunks += 1;
+            duty.fill(chunks);
+            duty.len()
+        };
+        let mut buffers = PcmBuffers::new();
+        assert!(buffers.start(&timing, &mut render));
+
+        // Woken up after the second sequence started playing the first buffer again, the next
+        // chunk goes to the buffer of the third sequence
+        assert!(!buffers.refill(2, &mut render));
+        assert_eq!(buffers.buffers()[0][0], 1);
+        assert_eq!(buffers.buffers()[1][0], 3);
+        assert!(!buffers.refill(3, &mut render));
+        assert_eq!(buffers.buffers()[0][0], 4);
+    }
+
+    #[test]
+    fn test_wav() {
+        let data = wav::<128>(8_000, 8, &[128, 255, 0]);
+        let clip = PcmClip::parse_wav(&data).unwrap();
+        assert_eq!(clip.samples(), &Samples::U8(&[128, 255, 0]));
+        assert_eq!(clip.rate(), 8_000);
+
+        let data = wav::<128>(16_000, 16, &[0x00, 0x80, 0xff, 0x7f]);
+        let clip = PcmClip::parse_wav(&data).unwrap();
+        assert_eq!(clip.samples(), &Samples::I16Le(&[0x00, 0x80, 0xff, 0x7f]));
+        assert_eq!(clip.duration_ms(), 0);
+
+        // Data sizes larger than the file, as written when streaming
+        let mut data = wav::<128>(8_000, 8, &[1, 2]);
+        let len = data.len();
+        data[len - 6..len - 2].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(PcmClip::parse_wav(&data).unwrap().samples(), &Samples::U8(&[1, 2]));
+    }
+
+    #[test]
+    fn test_wav_errors() {
+        assert_eq!(PcmClip::parse_wav(b"RIFF\0\0\0\0WAVX"), Err(PcmError::Wav));
+        assert_eq!(PcmClip::parse_wav(b"RIFF\0\0\0\0WAVE"), Err(PcmError::Data));
+
+        let mut data = wav::<128>(8_000, 24, &[0; 6]);
+        assert_eq!(PcmClip::parse_wav(&data), Err(PcmError::BitsPerSample(24)));
+        data[22] = 2;
+        assert_eq!(PcmClip::parse_wav(&data), Err(PcmError::Channels(2)));
+        data[20] = 3;
+        assert_eq!(PcmClip::parse_wav(&data), Err(PcmError::Format));
+
+        let data = wav::<128>(100_000, 8, &[0; 6]);
+        assert_eq!(PcmClip::parse_wav(&data), Err(PcmError::SampleRate(100_000)));
+
+        // The data chunk must come after the format chunk
+        let mut data = wav::<128>(8_000, 8, &[0; 6]);
+        data[12..16].copy_from_slice(b"junk");
+        assert_eq!(PcmClip::parse_wav(&data), Err(PcmError::Format));
+    }
+}
@@ -0,0 +1,175 @@
+//! Pitches and notes played by the speaker
+
+/// Represents any pitch
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[allow(missing_docs)]
+pub enum Pitch {
+    Silent,
+    Named(NamedPitch),
+    /// Hz
+    Frequency(u32),
+}
+
+/// Pitch for standard scale
+#[allow(dead_code, missing_docs)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[repr(u32)]
+pub enum NamedPitch {
+    C0 = 16,
+    CS0 = 17,
+    D0 = 18,
+    DS0 = 19,
+    E0 = 20,
+    F0 = 21,
+    FS0 = 23,
+    G0 = 24,
+    GS0 = 25,
+    A0 = 27,
+    AS0 = 29,
+    B0 = 30,
+    C1 = 32,
+    CS1 = 34,
+    D1 = 36,
+    DS1 = 38,
+    E1 = 41,
+    F1 = 43,
+    FS1 = 46,
+    G1 = 49,
+    GS1 = 51,
+    A1 = 55,
+    AS1 = 58,
+    B1 = 61,
+    C2 = 65,
+    CS2 = 69,
+    D2 = 73,
+    DS2 = 77,
+    E2 = 82,
+    F2 = 87,
+    FS2 = 92,
+    G2 = 98,
+    GS2 = 103,
+    A2 = 110,
+    AS2 = 116,
+    B2 = 123,
+    C3 = 130,
+    CS3 = 138,
+    D3 = 146,
+    DS3 = 155,
+    E3 = 164,
+    F3 = 174,
+    FS3 = 185,
+    G3 = 196,
+    GS3 = 207,
+    A3 = 220,
+    AS3 = 233,
+    B3 = 246,
+    C4 = 261,
+    CS4 = 277,
+    D4 = 293,
+    DS4 = 311,
+    E4 = 329,
+    F4 = 349,
+    FS4 = 369,
+    G4 = 392,
+    GS4 = 415,
+    A4 = 440,
+    AS4 = 466,
+    B4 = 493,
+    C5 = 523,
+    CS5 = 554,
+    D5 = 587,
+    DS5 = 622,
+    E5 = 659,
+    F5 = 698,
+    FS5 = 739,
+    G5 = 783,
+    GS5 = 830,
+    A5 = 880,
+    AS5 = 932,
+    B5 = 987,
+    C6 = 1046,
+    CS6 = 1108,
+    D6 = 1174,
+    DS6 = 1244,
+    E6 = 1318,
+    F6 = 1396,
+    FS6 = 1479,
+    G6 = 1567,
+    GS6 = 1661,
+    A6 = 1760,
+    AS6 = 1864,
+    B6 = 1975,
+    C7 = 2093,
+    CS7 = 2217,
+    D7 = 2349,
+    DS7 = 2489,
+    E7 = 2637,
+    F7 = 2793,
+    FS7 = 2959,
+    G7 = 3135,
+    GS7 = 3322,
+    A7 = 3520,
+    AS7 = 3729,
+    B7 = 3951,
+    C8 = 4186,
+    CS8 = 4434,
+    D8 = 4698,
+    DS8 = 4978,
+    E8 = 5274,
+    F8 = 5587,
+    FS8 = 5919,
+    G8 = 6271,
+    GS8 = 6644,
+    A8 = 7040,
+    AS8 = 7458,
+    B8 = 7902,
+}
+
+impl NamedPitch {
+    // All pitches in order, 12 per octave
+    #[rustfmt::skip]
+    const ALL: [NamedPitch; 108] = [
+        NamedPitch::C0, NamedPitch::CS0, NamedPitch::D0, NamedPitch::DS0, NamedPitch::E0, NamedPitch::F0,
+        NamedPitch::FS0, NamedPitch::G0, NamedPitch::GS0, NamedPitch::A0, NamedPitch::AS0, NamedPitch::B0,
+        NamedPitch::C1, NamedPitch::CS1, NamedPitch::D1, NamedPitch::DS1, NamedPitch::E1, NamedPitch::F1,
+        NamedPitch::FS1, NamedPitch::G1, NamedPitch::GS1, NamedPitch::A1, NamedPitch::AS1, NamedPitch::B1,
+        NamedPitch::C2, NamedPitch::CS2, NamedPitch::D2, NamedPitch::DS2, NamedPitch::E2, NamedPitch::F2,
+        NamedPitch::FS2, NamedPitch::G2, NamedPitch::GS2, NamedPitch::A2, NamedPitch::AS2, NamedPitch::B2,
+        NamedPitch::C3, NamedPitch::CS3, NamedPitch::D3, NamedPitch::DS3, NamedPitch::E3, NamedPitch::F3,
+        NamedPitch::FS3, NamedPitch::G3, NamedPitch::GS3, NamedPitch::A3, NamedPitch::AS3, NamedPitch::B3,
+        NamedPitch::C4, NamedPitch::CS4, NamedPitch::D4, NamedPitch::DS4, NamedPitch::E4, NamedPitch::F4,
+        NamedPitch::FS4, NamedPitch::G4, NamedPitch::GS4, NamedPitch::A4, NamedPitch::AS4, NamedPitch::B4,
+        NamedPitch::C5, NamedPitch::CS5, NamedPitch::D5, NamedPitch::DS5, NamedPitch::E5, NamedPitch::F5,
+        NamedPitch::FS5, NamedPitch::G5, NamedPitch::GS5, NamedPitch::A5, NamedPitch::AS5, NamedPitch::B5,
+        NamedPitch::C6, NamedPitch::CS6, NamedPitch::D6, NamedPitch::DS6, NamedPitch::E6, NamedPitch::F6,
+        NamedPitch::FS6, NamedPitch::G6, NamedPitch::GS6, NamedPitch::A6, NamedPitch::AS6, NamedPitch::B6,
+        NamedPitch::C7, NamedPitch::CS7, NamedPitch::D7, NamedPitch::DS7, NamedPitch::E7, NamedPitch::F7,
+        NamedPitch::FS7, NamedPitch::G7, NamedPitch::GS7, NamedPitch::A7, NamedPitch::AS7, NamedPitch::B7,
+        NamedPitch::C8, NamedPitch::CS8, NamedPitch::D8, NamedPitch::DS8, NamedPitch::E8, NamedPitch::F8,
+        NamedPitch::FS8, NamedPitch::G8, NamedPitch::GS8, NamedPitch::A8, NamedPitch::AS8, NamedPitch::B8,
+    ];
+
+    /// Turn into Hz
+    #[must_use]
+    pub fn into_frequency(self) -> u32 {
+        self as u32
+    }
+
+    /// Pitch of the semitone in the octave, with C being semitone 0 and B semitone 11
+    pub fn from_note(octave: u32, semitone: u32) -> Option<Self> {
+        if semitone >= 12 {
+            return None;
+        }
+        Self::ALL.get(octave as usize * 12 + semitone as usize).copied()
+    }
+}
+
+impl From<NamedPitch> for Pitch {
+    fn from(value: NamedPitch) -> Self {
+        Self::Named(value)
+    }
+}
+
+/// A note is a pitch + a duration (ms)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Note(pub Pitch, pub u32);
@@ -0,0 +1,124 @@
+//! Background playback of melodies and sound effects
+//!
+//! An [`AudioPlayer`] owns the [`PwmSpeaker`] and plays the tracks requested through a
+//! [`SharedAudio`] from a dedicated task, so other tasks can start a tune or a sound effect
+//! without waiting for it to finish.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use microbit_bsp::embassy_nrf::pwm::{SimpleConfig, SimplePwm};
+//! use microbit_bsp::speaker::{AudioPlayer, Melody, PwmSpeaker, SharedAudio, SoundEffect, SoundExpression, Track};
+//! use microbit_bsp::Microbit;
+//!
+//! static AUDIO: SharedAudio = SharedAudio::new();
+//! const CHIME: SoundExpression = SoundExpression::new(&[SoundEffect {
+//!     freq_start: 900,
+//!     freq_end: 1300,
+//!     ..SoundEffect::DEFAULT
+//! }]);
+//!
+//! // Run from a dedicated task, e.g. one spawned with `embassy_executor`
+//! async fn play(mut player: AudioPlayer<'static, 4>) -> ! {
+//!     player.run().await
+//! }
+//!
+//! async fn app() {
+//!     let tune = Melody::parse("c4:4 e g c5:8").unwrap();
+//!     let ding = Melody::parse("c6:2").unwrap();
+//!     AUDIO.play(Track::music(1, tune).looping()).await;
+//!
+//!     // Interrupts the tune, which resumes afterwards
+//!     let mut events = AUDIO.subscribe().unwrap();
+//!     AUDIO.play(Track::effect(2, ding)).await;
+//!     events.wait_for_track(2).await;
+//!
+//!     // Sound expressions are played as tones following their frequencies
+//!     AUDIO.play(Track::effect(3, CHIME)).await;
+//! }
+//!
+//! let board = Microbit::default();
+//! let pwm = SimplePwm::new_1ch(board.pwm0, board.speaker, &SimpleConfig::default());
+//! let player: AudioPlayer<'static, 4> = AudioPlayer::new(PwmSpeaker::new(pwm), &AUDIO);
+//! ```
+use embassy_time::{with_deadline, Duration, Instant};
+
+use super::{AudioCommand, AudioEvent, Pitch, Playlist, PwmSpeaker, SharedAudio};
+
+/// Player engine playing the tracks requested through a [`SharedAudio`]. Keeps up to `N` tracks,
+/// including the tracks waiting for a higher priority track to finish.
+pub struct AudioPlayer<'a, const N: usize> {
+    speaker: PwmSpeaker<'a>,
+    shared: &'a SharedAudio,
+    playlist: Playlist<N>,
+}
+
+impl<'a, const N: usize> AudioPlayer<'a, N> {
+    /// Create a player for the speaker, taking requests from the shared channels
+    pub fn new(speaker: PwmSpeaker<'a>, shared: &'a SharedAudio) -> Self {
+        Self {
+            speaker,
+            shared,
+            playlist: Playlist::new(),
+        }
+    }
+
+    // Apply a request, returning true if the playing track changed
+    fn handle(&mut self, command: AudioCommand) -> bool {
+        let shared = self.shared;
+        match command {
+            AudioCommand::Play(track) => match self.playlist.add(track, |e| shared.report(e)) {
+                Ok(interrupted) => interrupted,
+                Err(track) => {
+                    shared.report(AudioEvent::Dropped(track.id()));
+                    false
+                }
+            },
+            AudioCommand::Stop(id) => self.playlist.stop(id, |e| shared.report(e)),
+            AudioCommand::StopAll => self.playlist.stop_all(|e| shared.report(e)),
+        }
+    }
+
+    /// Play the next note of the playlist, or wait for a request if there is nothing to play.
+    ///
+    /// The note is cut short when a request changes the playing track. An interrupted track
+    /// plays the rest of the note when it resumes.
+    pub async fn step(&mut self) {
+        while let Some(command) = self.shared.try_receive_command() {
+            self.handle(command);
+        }
+
+        let shared = self.shared;
+        let Some(note) = self.playlist.next_note(|e| shared.report(e)) else {
+            let command = self.shared.receive_command().await;
+            self.handle(command);
+            return;
+        };
+
+        if note.0 != Pitch::Silent {
+            self.speaker.start_note(note.0);
+        }
+        let end = Instant::now() + Duration::from_millis(u64::from(note.1));
+        while let Ok(command) = with_deadline(end, self.shared.receive_command()).await {
+            if self.handle(command) {
+                let rest = end.saturating_duration_since(Instant::now()).as_millis();
+                self.playlist.cut_note(rest as u32);
+                break;
+            }
+        }
+        self.speaker.stop();
+    }
+
+    /// Keep playing requested tracks forever. Intended to run in a dedicated task.
+    pub async fn run(&mut self) -> ! {
+        loop {
+            self.step().await;
+        }
+    }
+
+    /// Stop playing and return the speaker. Tracks left in the playlist are not reported.
+    pub fn into_inner(mut self) -> PwmSpeaker<'a> {
+        self.speaker.stop();
+        self.speaker
+    }
+}
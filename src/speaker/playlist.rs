@@ -0,0 +1,687 @@
+//! Scheduling of melodies and sound effects for background playback
+//!
+//! Tasks request tracks through a [`SharedAudio`], which are played one at a time by an
+//! [`AudioPlayer`](super::AudioPlayer) running in a dedicated task. Tracks wait in a
+//! [`Playlist`] ordered by priority. A track with a higher priority than the playing one, such as
+//! a sound effect, interrupts it, and the interrupted track resumes with the rest of the note it
+//! was playing once the higher priority tracks have finished. When the playlist is full, the most recent track with
+//! the lowest priority makes room for tracks with a higher priority.
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::Channel;
+use embassy_sync::pubsub::{PubSubChannel, Subscriber, WaitResult};
+
+use super::{Melody, Note, Notes, SoundExpression, SweepNotes};
+
+/// Priority of background music created with [`Track::music`]
+pub const MUSIC_PRIORITY: u8 = 0;
+
+/// Priority of sound effects created with [`Track::effect`]
+pub const EFFECT_PRIORITY: u8 = 128;
+
+/// Number of commands that can be waiting for the player
+pub const AUDIO_COMMANDS: usize = 4;
+
+/// Number of events that can be waiting to be received. When a new event is reported, the
+/// oldest event is dropped for the receivers that have not received it yet.
+pub const AUDIO_EVENTS: usize = 8;
+
+/// Number of [`AudioEvents`] receivers that can exist at the same time
+pub const AUDIO_SUBSCRIBERS: usize = 4;
+
+/// What a track plays
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Source {
+    /// A melody, played note by note
+    Melody(Melody<'static>),
+    /// A sound expression, played as the tones of [`SoundExpression::notes`]
+    Sound(SoundExpression<'static>),
+}
+
+impl Source {
+    fn notes(&self) -> SourceNotes {
+        match self {
+            Source::Melody(melody) => SourceNotes::Melody(melody.notes()),
+            Source::Sound(sound) => SourceNotes::Sound(sound.notes()),
+        }
+    }
+}
+
+impl From<Melody<'static>> for Source {
+    fn from(melody: Melody<'static>) -> Self {
+        Source::Melody(melody)
+    }
+}
+
+impl From<SoundExpression<'static>> for Source {
+    fn from(sound: SoundExpression<'static>) -> Self {
+        Source::Sound(sound)
+    }
+}
+
+// Notes left to play from a source
+enum SourceNotes {
+    Melody(Notes<'static>),
+    Sound(SweepNotes<'static>),
+}
+
+impl Iterator for SourceNotes {
+    type Item = Note;
+
+    fn next(&mut self) -> Option<Note> {
+        match self {
+            SourceNotes::Melody(notes) => notes.next(),
+            SourceNotes::Sound(notes) => notes.next(),
+        }
+    }
+}
+
+/// A melody or a sound to play, identified by an id chosen by the application
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Track {
+    id: u16,
+    source: Source,
+    priority: u8,
+    looping: bool,
+}
+
+impl Track {
+    /// Create background music from a melody or a sound, played after the music requested
+    /// before it
+    pub fn music(id: u16, source: impl Into<Source>) -> Self {
+        Self {
+            id,
+            source: source.into(),
+            priority: MUSIC_PRIORITY,
+            looping: false,
+        }
+    }
+
+    /// Create a sound effect from a melody or a sound, interrupting the music while it plays
+    pub fn effect(id: u16, source: impl Into<Source>) -> Self {
+        Self {
+            priority: EFFECT_PRIORITY,
+            ..Self::music(id, source)
+        }
+    }
+
+    /// Change the priority. Tracks interrupt tracks with a lower priority, and wait for tracks
+    /// with the same or a higher priority.
+    pub fn with_priority(self, priority: u8) -> Self {
+        Self { priority, ..self }
+    }
+
+    /// Repeat the track until it is stopped
+    pub fn looping(self) -> Self {
+        Self { looping: true, ..self }
+    }
+
+    /// Id of the track
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// What the track plays
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Priority of the track
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    /// Check if the track is repeated until it is stopped
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+}
+
+/// Requests handled by the audio player
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AudioCommand {
+    /// Add the track to the playlist
+    Play(Track),
+    /// Remove all tracks with the id from the playlist
+    Stop(u16),
+    /// Remove all tracks from the playlist
+    StopAll,
+}
+
+/// Events reported by the audio player
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AudioEvent {
+    /// The track with the id played all its notes
+    Finished(u16),
+    /// The track with the id was stopped before it finished
+    Stopped(u16),
+    /// The track with the id was not played to the end, since the playlist was full
+    Dropped(u16),
+}
+
+impl AudioEvent {
+    /// Id of the track the event is about
+    pub fn id(&self) -> u16 {
+        match *self {
+            Self::Finished(id) | Self::Stopped(id) | Self::Dropped(id) => id,
+        }
+    }
+}
+
+// A track in the playlist with the notes left to play, starting with the rest of a note cut
+// off by an interruption
+struct Entry {
+    track: Track,
+    notes: SourceNotes,
+    playing: Option<Note>,
+    rest: Option<Note>,
+}
+
+/// Tracks waiting to be played, ordered by priority. Holds up to `N` tracks, including the
+/// tracks interrupted by higher priority tracks.
+pub struct Playlist<const N: usize> {
+    entries: heapless::Vec<Entry, N>,
+}
+
+impl<const N: usize> Playlist<N> {
+    /// Create an empty playlist
+    pub const fn new() -> Self {
+        Self {
+            entries: heapless::Vec::new(),
+        }
+    }
+
+    /// Add the track after all tracks with the same or a higher priority. Returns `true` if
+    /// the track interrupts the playing track.
+    ///
+    /// If the playlist is full, the last track with the lowest priority is removed and reported
+    /// as dropped to make room, as long as its priority is lower than the priority of the track.
+    /// Otherwise the track is returned.
+    pub fn add(&mut self, track: Track, mut events: impl FnMut(AudioEvent)) -> Result<bool, Track> {
+        if self.entries.is_full() {
+            let lowest = self.entries.iter().map(|e| e.track.priority).min();
+            match self.entries.iter().rposition(|e| Some(e.track.priority) == lowest) {
+                Some(last) if self.entries[last].track.priority < track.priority => {
+                    let entry = self.entries.remove(last);
+                    events(AudioEvent::Dropped(entry.track.id));
+                }
+                _ => return Err(track),
+            }
+        }
+
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.track.priority < track.priority)
+            .unwrap_or(self.entries.len());
+        let entry = Entry {
+            track,
+            notes: track.source.notes(),
+            playing: None,
+            rest: None,
+        };
+        self.entries.insert(index, entry).map_err(|e| e.track)?;
+        Ok(index == 0)
+    }
+
+    /// Remove all tracks with the id, reporting them as stopped. Returns `true` if the playing
+    /// track was stopped.
+    pub fn stop(&mut self, id: u16, mut events: impl FnMut(AudioEvent)) -> bool {
+        let playing = self.current().is_some_and(|t| t.id == id);
+        let before = self.entries.len();
+        self.entries.retain(|e| e.track.id != id);
+        for _ in self.entries.len()..before {
+            events(AudioEvent::Stopped(id));
+        }
+        playing
+    }
+
+    /// Remove all tracks, reporting them as stopped. Returns `true` if a track was playing.
+    pub fn stop_all(&mut self, mut events: impl FnMut(AudioEvent)) -> bool {
+        let playing = !self.entries.is_empty();
+        for entry in self.entries.iter() {
+            events(AudioEvent::Stopped(entry.track.id));
+        }
+        self.entries.clear();
+        playing
+    }
+
+    /// The track being played
+    pub fn current(&self) -> Option<&Track> {
+        self.entries.first().map(|e| &e.track)
+    }
+
+    /// Number of tracks in the playlist
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if there are no tracks to play
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Advance to the next note to play, reporting tracks that finished on the way
+    pub fn next_note(&mut self, mut events: impl FnMut(AudioEvent)) -> Option<Note> {
+        for entry in self.entries.iter_mut() {
+            entry.playing = None;
+        }
+        loop {
+            let entry = self.entries.first_mut()?;
+            if let Some(note) = entry.rest.take().or_else(|| entry.notes.next()) {
+                entry.playing = Some(note);
+                return Some(note);
+            }
+
+            // Tracks without notes would loop forever without playing anything
+            if entry.track.looping && entry.track.source.notes().next().is_some() {
+                entry.notes = entry.track.source.notes();
+            } else {
+                let entry = self.entries.remove(0);
+                events(AudioEvent::Finished(entry.track.id));
+            }
+        }
+    }
+
+    /// Keep the last `ms` milliseconds of the note returned by [`next_note`](Self::next_note),
+    /// when it is cut off by an interruption. The track plays the rest of the note first when it
+    /// resumes. Nothing is kept if the track was stopped.
+    pub fn cut_note(&mut self, ms: u32) {
+        for entry in self.entries.iter_mut() {
+            if let Some(Note(pitch, _)) = entry.playing.take() {
+                entry.rest = (ms > 0).then_some(Note(pitch, ms));
+            }
+        }
+    }
+}
+
+impl<const N: usize> Default for Playlist<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+type EventChannel = PubSubChannel<CriticalSectionRawMutex, AudioEvent, AUDIO_EVENTS, AUDIO_SUBSCRIBERS, 0>;
+
+/// Receiver of the events reported by the audio player, created with [`SharedAudio::subscribe`].
+///
+/// Every receiver gets all events reported while it exists, so tasks waiting for different
+/// tracks do not take events from each other.
+pub struct AudioEvents<'a> {
+    subscriber: Subscriber<'a, CriticalSectionRawMutex, AudioEvent, AUDIO_EVENTS, AUDIO_SUBSCRIBERS, 0>,
+}
+
+impl AudioEvents<'_> {
+    /// Wait for the next playback event. Events dropped since they were not received in time
+    /// are skipped.
+    pub async fn next(&mut self) -> AudioEvent {
+        loop {
+            if let WaitResult::Message(event) = self.subscriber.next_message().await {
+                return event;
+            }
+        }
+    }
+
+    /// Return the next playback event, if any
+    pub fn try_next(&mut self) -> Option<AudioEvent> {
+        self.subscriber.try_next_message_pure()
+    }
+
+    /// Wait until the track with the id has finished, been stopped or dropped. Events of other
+    /// tracks received in the meantime are skipped by this receiver only.
+    pub async fn wait_for_track(&mut self, id: u16) -> AudioEvent {
+        loop {
+            let event = self.next().await;
+            if event.id() == id {
+                return event;
+            }
+        }
+    }
+}
+
+/// Channels between the application tasks and an [`AudioPlayer`](super::AudioPlayer)
+pub struct SharedAudio {
+    commands: Channel<CriticalSectionRawMutex, AudioCommand, AUDIO_COMMANDS>,
+    events: EventChannel,
+}
+
+impl SharedAudio {
+    /// Create the channels
+    pub const fn new() -> Self {
+        Self {
+            commands: Channel::new(),
+            events: PubSubChannel::new(),
+        }
+    }
+
+    /// Request the track to be played, waiting if the player is busy with other requests
+    pub async fn play(&self, track: Track) {
+        self.commands.send(AudioCommand::Play(track)).await;
+    }
+
+    /// Request the track to be played, returning the track if the player is busy
+    pub fn try_play(&self, track: Track) -> Result<(), Track> {
+        self.commands.try_send(AudioCommand::Play(track)).map_err(|_| track)
+    }
+
+    /// Stop all tracks with the id
+    pub async fn stop(&self, id: u16) {
+        self.commands.send(AudioCommand::Stop(id)).await;
+    }
+
+    /// Stop all tracks
+    pub async fn stop_all(&self) {
+        self.commands.send(AudioCommand::StopAll).await;
+    }
+
+    /// Create a receiver for the events reported from now on, or `None` if there are already
+    /// [`AUDIO_SUBSCRIBERS`] receivers. Subscribe before requesting a track to be sure to
+    /// receive its events.
+    pub fn subscribe(&self) -> Option<AudioEvents<'_>> {
+        let subscriber = self.events.subscriber().ok()?;
+        Some(AudioEvents { subscriber })
+    }
+
+    pub(crate) async fn receive_command(&self) -> AudioCommand {
+        self.commands.receive().await
+    }
+
+    pub(crate) fn try_receive_command(&self) -> Option<AudioCommand> {
+        self.commands.try_receive().ok()
+    }
+
+    // Report an event to all receivers, dropping the oldest event if the queue is full
+    pub(crate) fn report(&self, event: AudioEvent) {
+        self.events.immediate_publisher().publish_immediate(event);
+    }
+}
+
+impl Default for SharedAudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use super::*;
+    use crate::speaker::{Curve, NamedPitch, Pitch, SoundEffect, Waveform};
+
+    const SWEEP: SoundExpression = SoundExpression::new(&[SoundEffect {
+        freq_start: 400,
+        freq_end: 1600,
+        duration_ms: 600,
+        vol_end: 150,
+        shape: Curve::Linear,
+        ..SoundEffect::DEFAULT
+    }]);
+    const CHIRP: SoundExpression = SoundExpression::new(&[SoundEffect {
+        waveform: Waveform::Sine,
+        freq_start: 1400,
+        freq_end: 1800,
+        duration_ms: 90,
+        ..SoundEffect::DEFAULT
+    }]);
+
+    // Frequency of the next note, with 0 Hz for rests
+    fn next<const N: usize>(playlist: &mut Playlist<N>, events: &mut heapless::Vec<AudioEvent, 8>) -> Option<u32> {
+        playlist
+            .next_note(|e| events.push(e).unwrap())
+            .map(|Note(pitch, _)| match pitch {
+                Pitch::Named(n) => n.into_frequency(),
+                Pitch::Silent => 0,
+                Pitch::Frequency(hz) => hz,
+            })
+    }
+
+    #[test]
+    fn test_queue() {
+        let mut playlist: Playlist<2> = Playlist::new();
+        let mut events = heapless::Vec::new();
+        assert_eq!(next(&mut playlist, &mut events), None);
+
+        assert_eq!(
+            playlist.add(Track::music(1, Melody::parse("c4:4 e").unwrap()), |_| {}),
+            Ok(true)
+        );
+        assert_eq!(
+            playlist.add(Track::music(2, Melody::parse("g4").unwrap()), |_| {}),
+            Ok(false)
+        );
+        let full = Track::music(3, Melody::parse("a4").unwrap());
+        assert_eq!(playlist.add(full, |_| {}), Err(full));
+        assert_eq!(playlist.len(), 2);
+
+        // Tracks with the same priority play in order
+        assert_eq!(next(&mut playlist, &mut events), Some(261));
+        assert_eq!(next(&mut playlist, &mut events), Some(329));
+        assert_eq!(next(&mut playlist, &mut events), Some(392));
+        assert_eq!(events, [AudioEvent::Finished(1)]);
+        assert_eq!(next(&mut playlist, &mut events), None);
+        assert_eq!(events, [AudioEvent::Finished(1), AudioEvent::Finished(2)]);
+        assert!(playlist.is_empty());
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let mut playlist: Playlist<4> = Playlist::new();
+        let mut events = heapless::Vec::new();
+        playlist
+            .add(Track::music(1, Melody::parse("c4:4 e g").unwrap()), |_| {})
+            .unwrap();
+        assert_eq!(next(&mut playlist, &mut events), Some(261));
+
+        // The effect interrupts the music, which resumes with the rest of its note
+        assert_eq!(
+            playlist.add(Track::effect(2, Melody::parse("c6:2 r").unwrap()), |_| {}),
+            Ok(true)
+        );
+        playlist.cut_note(300);
+        assert_eq!(playlist.current().map(Track::id), Some(2));
+        assert_eq!(
+            playlist.add(Track::music(3, Melody::parse("a4").unwrap()), |_| {}),
+            Ok(false)
+        );
+        assert_eq!(next(&mut playlist, &mut events), Some(1046));
+        assert_eq!(next(&mut playlist, &mut events), Some(0));
+        assert_eq!(
+            playlist.next_note(|e| events.push(e).unwrap()),
+            Some(Note(Pitch::Named(NamedPitch::C4), 300))
+        );
+        assert_eq!(next(&mut playlist, &mut events), Some(329));
+        assert_eq!(events, [AudioEvent::Finished(2)]);
+
+        // Lower priorities never interrupt
+        let quiet = Track::effect(4, Melody::parse("b4").unwrap()).with_priority(1);
+        assert_eq!(
+            playlist.add(Track::effect(5, Melody::parse("b5").unwrap()), |_| {}),
+            Ok(true)
+        );
+        assert_eq!(playlist.add(quiet, |_| {}), Ok(false));
+        assert_eq!(next(&mut playlist, &mut events), Some(987));
+        assert_eq!(next(&mut playlist, &mut events), Some(493));
+        assert_eq!(next(&mut playlist, &mut events), Some(392));
+        assert_eq!(next(&mut playlist, &mut events), Some(440));
+        assert_eq!(next(&mut playlist, &mut events), None);
+        assert_eq!(
+            events,
+            [
+                AudioEvent::Finished(2),
+                AudioEvent::Finished(5),
+                AudioEvent::Finished(4),
+                AudioEvent::Finished(1),
+                AudioEvent::Finished(3)
+            ]
+        );
+
+        // A note interrupted again resumes with what is left of its rest
+        playlist
+            .add(Track::music(1, Melody::parse("e4:8").unwrap()), |_| {})
+            .unwrap();
+        assert_eq!(next(&mut playlist, &mut events), Some(329));
+        for (id, ms) in [(6, 600), (7, 200)] {
+            playlist
+                .add(Track::effect(id, Melody::parse("c6").unwrap()), |_| {})
+                .unwrap();
+            playlist.cut_note(ms);
+            assert_eq!(next(&mut playlist, &mut events), Some(1046));
+            assert_eq!(playlist.next_note(|_| {}), Some(Note(Pitch::Named(NamedPitch::E4), ms)));
+        }
+
+        // Stopped tracks keep nothing
+        playlist
+            .add(Track::effect(8, Melody::parse("c6").unwrap()), |_| {})
+            .unwrap();
+        assert_eq!(next(&mut playlist, &mut events), Some(1046));
+        playlist.stop(8, |_| {});
+        playlist.cut_note(100);
+        assert_eq!(next(&mut playlist, &mut events), None);
+    }
+
+    #[test]
+    fn test_full() {
+        let mut playlist: Playlist<3> = Playlist::new();
+        let mut events: heapless::Vec<AudioEvent, 8> = heapless::Vec::new();
+        playlist
+            .add(Track::music(1, Melody::parse("c4").unwrap()), |_| {})
+            .unwrap();
+        playlist
+            .add(Track::music(2, Melody::parse("e4").unwrap()), |_| {})
+            .unwrap();
+        playlist.add(Track::effect(3, SWEEP), |_| {}).unwrap();
+
+        // The last queued music makes room for the effect
+        let effect = Track::effect(4, CHIRP);
+        assert_eq!(playlist.add(effect, |e| events.push(e).unwrap()), Ok(false));
+        assert_eq!(events, [AudioEvent::Dropped(2)]);
+        assert_eq!(playlist.current().map(Track::id), Some(3));
+
+        // Tracks are only dropped for tracks with a higher priority
+        playlist.stop(3, |_| {});
+        playlist
+            .add(Track::effect(5, Melody::parse("g4").unwrap()), |_| {})
+            .unwrap();
+        let music = Track::music(6, Melody::parse("a4").unwrap());
+        assert_eq!(playlist.add(music, |e| events.push(e).unwrap()), Err(music));
+        let effect = Track::effect(7, Melody::parse("b4").unwrap());
+        assert_eq!(playlist.add(effect, |e| events.push(e).unwrap()), Ok(false));
+        assert_eq!(events, [AudioEvent::Dropped(2), AudioEvent::Dropped(1)]);
+        let effect = Track::effect(8, Melody::parse("b4").unwrap());
+        assert_eq!(playlist.add(effect, |e| events.push(e).unwrap()), Err(effect));
+    }
+
+    #[test]
+    fn test_sound() {
+        let mut playlist: Playlist<2> = Playlist::new();
+        let mut events = heapless::Vec::new();
+        let track = Track::effect(1, SWEEP);
+        assert_eq!(track.source(), &Source::Sound(SWEEP));
+        playlist.add(track, |_| {}).unwrap();
+
+        // Sounds are played as the tones following their frequencies
+        for Note(pitch, _) in SWEEP.notes() {
+            let Pitch::Frequency(hz) = pitch else { unreachable!() };
+            assert_eq!(next(&mut playlist, &mut events), Some(hz));
+        }
+        assert_eq!(next(&mut playlist, &mut events), None);
+        assert_eq!(events, [AudioEvent::Finished(1)]);
+    }
+
+    #[test]
+    fn test_looping() {
+        let mut playlist: Playlist<4> = Playlist::new();
+        let mut events = heapless::Vec::new();
+        let track = Track::music(1, Melody::parse("c4:4 e").unwrap()).looping();
+        assert!(track.is_looping());
+        playlist.add(track, |_| {}).unwrap();
+        for _ in 0..3 {
+            assert_eq!(next(&mut playlist, &mut events), Some(261));
+            assert_eq!(next(&mut playlist, &mut events), Some(329));
+        }
+        assert!(events.is_empty());
+
+        // Stopping a waiting track does not change the playing track
+        playlist
+            .add(Track::music(2, Melody::parse("g4").unwrap()), |_| {})
+            .unwrap();
+        playlist
+            .add(Track::music(2, Melody::parse("a4").unwrap()), |_| {})
+            .unwrap();
+        assert!(!playlist.stop(2, |e| events.push(e).unwrap()));
+        assert_eq!(events, [AudioEvent::Stopped(2), AudioEvent::Stopped(2)]);
+        assert!(playlist.stop(1, |e| events.push(e).unwrap()));
+        assert_eq!(next(&mut playlist, &mut events), None);
+
+        // Looping melodies without notes finish right away
+        events.clear();
+        playlist
+            .add(Track::music(3, Melody::parse("").unwrap()).looping(), |_| {})
+            .unwrap();
+        assert_eq!(next(&mut playlist, &mut events), None);
+        assert_eq!(events, [AudioEvent::Finished(3)]);
+    }
+
+    #[test]
+    fn test_stop_all() {
+        let mut playlist: Playlist<4> = Playlist::new();
+        let mut events: heapless::Vec<AudioEvent, 8> = heapless::Vec::new();
+        assert!(!playlist.stop_all(|e| events.push(e).unwrap()));
+        playlist
+            .add(Track::music(1, Melody::parse("c4").unwrap()), |_| {})
+            .unwrap();
+        playlist
+            .add(Track::effect(2, Melody::parse("c5").unwrap()), |_| {})
+            .unwrap();
+        assert!(playlist.stop_all(|e| events.push(e).unwrap()));
+        assert_eq!(events, [AudioEvent::Stopped(2), AudioEvent::Stopped(1)]);
+        assert!(playlist.current().is_none());
+    }
+
+    #[test]
+    fn test_shared() {
+        let shared = SharedAudio::new();
+        let track = Track::effect(7, Melody::parse("c4").unwrap());
+        for _ in 0..AUDIO_COMMANDS {
+            shared.try_play(track).unwrap();
+        }
+        assert_eq!(shared.try_play(track), Err(track));
+        assert_eq!(shared.try_receive_command(), Some(AudioCommand::Play(track)));
+
+        // Events are dropped when nobody receives them
+        shared.report(AudioEvent::Finished(99));
+        let mut events = shared.subscribe().unwrap();
+        assert_eq!(events.try_next(), None);
+
+        // The newest events are kept when the receiver falls behind
+        for id in 0..AUDIO_EVENTS as u16 + 2 {
+            shared.report(AudioEvent::Finished(id));
+        }
+        for id in 2..AUDIO_EVENTS as u16 + 2 {
+            assert_eq!(events.next().now_or_never(), Some(AudioEvent::Finished(id)));
+        }
+        assert_eq!(events.try_next(), None);
+        assert_eq!(AudioEvent::Dropped(3).id(), 3);
+    }
+
+    #[test]
+    fn test_wait_for_track() {
+        let shared = SharedAudio::new();
+        let mut first = shared.subscribe().unwrap();
+        let mut second = shared.subscribe().unwrap();
+        shared.report(AudioEvent::Finished(1));
+        shared.report(AudioEvent::Stopped(2));
+        shared.report(AudioEvent::Dropped(3));
+
+        // Waiting for a track does not take the events of other tracks from other receivers
+        assert_eq!(first.wait_for_track(2).now_or_never(), Some(AudioEvent::Stopped(2)));
+        assert_eq!(second.wait_for_track(1).now_or_never(), Some(AudioEvent::Finished(1)));
+        assert_eq!(second.wait_for_track(2).now_or_never(), Some(AudioEvent::Stopped(2)));
+        assert_eq!(first.wait_for_track(1).now_or_never(), None);
+        assert_eq!(second.try_next(), Some(AudioEvent::Dropped(3)));
+
+        let _others: [_; AUDIO_SUBSCRIBERS - 2] = core::array::from_fn(|_| shared.subscribe().unwrap());
+        assert!(shared.subscribe().is_none());
+    }
+}
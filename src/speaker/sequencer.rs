@@ -0,0 +1,219 @@
+//! Speakers playing sequences loaded by the PWM with DMA
+//!
+//! The [`SequenceSpeaker`] plays the steps of a [`ToneSequence`], so notes, glissandos and
+//! arpeggios keep exact timing without gaps between the notes, even while the CPU is busy or
+//! asleep. The [`PcmSpeaker`] plays [`PcmClip`]s and synthesized [`SoundExpression`]s by varying
+//! the duty cycle of the PWM, alternating between two buffers so the playback never stops.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use microbit_bsp::speaker::{
+//!     Melody, PcmClip, PcmSpeaker, SequenceSpeaker, SoundEffect, SoundExpression, ToneSequence, WORDS_PER_STEP,
+//! };
+//! use microbit_bsp::Microbit;
+//!
+//! const CHIME: SoundExpression = SoundExpression::new(&[SoundEffect {
+//!     freq_start: 900,
+//!     freq_end: 1300,
+//!     ..SoundEffect::DEFAULT
+//! }]);
+//!
+//! async fn play() {
+//!     let board = Microbit::default();
+//!     let mut speaker = SequenceSpeaker::new(board.pwm0, board.speaker).unwrap();
+//!
+//!     // The steps are loaded from RAM
+//!     let mut words = [0; 1024 * WORDS_PER_STEP];
+//!     let mut tones = ToneSequence::new(&mut words, 3);
+//!     tones.melody(&Melody::parse("c4:2 e g c5:4").unwrap()).unwrap();
+//!     speaker.play(&tones).await.unwrap();
+//! }
+//!
+//! async fn say_hello() {
+//!     let board = Microbit::default();
+//!     let mut speaker = PcmSpeaker::new(board.pwm1, board.speaker, board.timer0, board.ppi_ch0, board.ppi_ch1);
+//!     # const HELLO: &[u8] = &[];
+//!     // e.g. `include_bytes!("hello.wav")`
+//!     let clip = PcmClip::parse_wav(HELLO).unwrap();
+//!     speaker.play(&clip).await.unwrap();
+//!     speaker.play_sound(&CHIME).await.unwrap();
+//! }
+//! ```
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use embassy_nrf::gpio::Pin as GpioPin;
+use embassy_nrf::ppi::{AnyConfigurableChannel, ConfigurableChannel, Ppi};
+use embassy_nrf::pwm::{
+    self, Prescaler, Sequence, SequenceConfig, SequenceLoad, SequenceMode, SequencePwm, Sequencer, SingleSequenceMode,
+    SingleSequencer, StartSequence,
+};
+use embassy_nrf::{timer, Peri};
+use embassy_time::{Duration, Timer};
+
+use super::{PcmBuffers, PcmClip, PcmTiming, SoundExpression, Synth, ToneSequence, PCM_CHUNK, SYNTH_RATE};
+
+/// Speaker driven by a PWM playing [`ToneSequence`]s without CPU involvement
+pub struct SequenceSpeaker<'a> {
+    pwm: SequencePwm<'a>,
+}
+
+impl<'a> SequenceSpeaker<'a> {
+    /// Create a new speaker on the pin, configuring the PWM to load the period of every step
+    /// from the sequence
+    pub fn new<T: pwm::Instance>(pwm: Peri<'a, T>, pin: Peri<'a, impl GpioPin>) -> Result<Self, pwm::Error> {
+        let mut config = pwm::Config::default();
+        config.prescaler = Prescaler::Div8;
+        config.sequence_load = SequenceLoad::Waveform;
+        Ok(Self {
+            pwm: SequencePwm::new_1ch(pwm, pin, config)?,
+        })
+    }
+
+    /// Play the tones once
+    pub async fn play(&mut self, tones: &ToneSequence<'_>) -> Result<(), pwm::Error> {
+        self.play_times(tones, 1).await
+    }
+
+    /// Play the tones repeatedly, without gaps between the repetitions.
+    ///
+    /// The PWM plays the sequence on its own and stops after the last repetition. The driver
+    /// does not expose the end of the sequence as an awaitable event, so the task sleeps on a
+    /// timer until the end computed from the steps, and only wakes up once. The timer does not
+    /// affect the timing of the tones, and waking up late does not extend them.
+    pub async fn play_times(&mut self, tones: &ToneSequence<'_>, times: u16) -> Result<(), pwm::Error> {
+        if tones.is_empty() || times == 0 {
+            return Ok(());
+        }
+
+        let mut config = SequenceConfig::default();
+        config.refresh = tones.refresh();
+        let sequencer = SingleSequencer::new(&mut self.pwm, tones.words(), config);
+        sequencer.start(SingleSequenceMode::Times(times))?;
+        Timer::after(Duration::from_micros(tones.duration_us() * u64::from(times))).await;
+        sequencer.stop();
+        Ok(())
+    }
+}
+
+/// Speaker driven by a PWM playing [`PcmClip`]s and [`SoundExpression`]s
+///
+/// The samples are converted to duty cycles in chunks of [`PCM_CHUNK`] samples, as the DMA can
+/// only read from RAM. The PWM loops over two sequences reading one buffer each, without
+/// stopping between them. The timer counts the ends of the sequences through the PPI channels,
+/// and the buffer of the sequence that ended is refilled with the next chunk while the other
+/// one plays. The task checks the count four times per chunk, so it has to run within three
+/// quarters of a chunk, 12 ms at 16 kHz, to keep up.
+pub struct PcmSpeaker<'a, T: pwm::Instance, P: GpioPin, TIM: timer::Instance> {
+    pwm: Peri<'a, T>,
+    pin: Peri<'a, P>,
+    timer: Peri<'a, TIM>,
+    ppi_ch0: Peri<'a, AnyConfigurableChannel>,
+    ppi_ch1: Peri<'a, AnyConfigurableChannel>,
+    buffers: PcmBuffers,
+}
+
+impl<'a, T: pwm::Instance, P: GpioPin, TIM: timer::Instance> PcmSpeaker<'a, T, P, TIM> {
+    /// Create a new speaker on the pin, counting the played buffers with the timer and the two
+    /// PPI channels. The PWM is configured for the sample rate of every clip played.
+    pub fn new(
+        pwm: Peri<'a, T>,
+        pin: Peri<'a, P>,
+        timer: Peri<'a, TIM>,
+        ppi_ch0: Peri<'a, impl ConfigurableChannel>,
+        ppi_ch1: Peri<'a, impl ConfigurableChannel>,
+    ) -> Self {
+        Self {
+            pwm,
+            pin,
+            timer,
+            ppi_ch0: ppi_ch0.into(),
+            ppi_ch1: ppi_ch1.into(),
+            buffers: PcmBuffers::new(),
+        }
+    }
+
+    /// Play the clip
+    pub async fn play(&mut self, clip: &PcmClip<'_>) -> Result<(), pwm::Error> {
+        let mut offset = 0;
+        self.stream(clip.timing(), |duty| {
+            let count = clip.render(offset, duty);
+            offset += count;
+            count
+        })
+        .await
+    }
+
+    /// Play the sound expression, rendered at [`SYNTH_RATE`]
+    pub async fn play_sound(&mut self, sound: &SoundExpression<'_>) -> Result<(), pwm::Error> {
+        let timing = PcmTiming::new(SYNTH_RATE).unwrap();
+        let mut synth = Synth::new(sound, SYNTH_RATE);
+        self.stream(&timing, |duty| synth.render(&timing, duty)).await
+    }
+
+    // Play the duty cycles produced by the render function until it produces none
+    async fn stream(
+        &mut self,
+        timing: &PcmTiming,
+        mut render: impl FnMut(&mut [u16]) -> usize,
+    ) -> Result<(), pwm::Error> {
+        if !self.buffers.start(timing, &mut render) {
+            return Ok(());
+        }
+
+        let mut config = pwm::Config::default();
+        config.prescaler = Prescaler::Div1;
+        config.max_duty = timing.counter_top();
+        config.sequence_load = SequenceLoad::Common;
+        let mut pwm = SequencePwm::new_1ch(self.pwm.reborrow(), self.pin.reborrow(), config)?;
+
+        // Count the ends of both sequences
+        let counter = timer::Timer::new_counter(self.timer.reborrow());
+        let mut seq0_end = Ppi::new_one_to_one(self.ppi_ch0.reborrow(), pwm.event_seq_end(), counter.task_count());
+        let mut seq1_end = Ppi::new_one_to_one(self.ppi_ch1.reborrow(), pwm.event_seq1_end(), counter.task_count());
+        seq0_end.enable();
+        seq1_end.enable();
+        counter.clear();
+        counter.start();
+
+        let sequence_config = || {
+            let mut config = SequenceConfig::default();
+            config.refresh = timing.refresh();
+            config
+        };
+        let [front, back] = self.buffers.buffers();
+        let sequencer = Sequencer::new(
+            &mut pwm,
+            Sequence::new(front, sequence_config()),
+            Some(Sequence::new(back, sequence_config())),
+        );
+        sequencer.start(StartSequence::Zero, SequenceMode::Infinite)?;
+        // Dropping the sequencer would stop the PWM, which keeps looping over the buffers
+        // refilled below until it is dropped once all samples have been played
+        core::mem::forget(sequencer);
+
+        let poll = Duration::from_micros(timing.duration_us(PCM_CHUNK) / 4);
+        loop {
+            Timer::after(poll).await;
+            let ended = counter.cc(0).capture();
+            if self.buffers.refill(ended, &mut render) {
+                return Ok(());
+            }
+            // The buffers are written before the DMA reads them
+            compiler_fence(Ordering::SeqCst);
+        }
+    }
+
+    /// Return the PWM, the pin, the timer and the PPI channels
+    pub fn into_inner(
+        self,
+    ) -> (
+        Peri<'a, T>,
+        Peri<'a, P>,
+        Peri<'a, TIM>,
+        Peri<'a, AnyConfigurableChannel>,
+        Peri<'a, AnyConfigurableChannel>,
+    ) {
+        (self.pwm, self.pin, self.timer, self.ppi_ch0, self.ppi_ch1)
+    }
+}
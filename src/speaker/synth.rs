@@ -0,0 +1,481 @@
+//! Sound effects synthesizer, like `SoundEffect` in MicroPython and MakeCode
+//!
+//! A [`SoundEffect`] sweeps the frequency and the volume of a waveform over its duration, and a
+//! [`SoundExpression`] plays a series of effects. The built-in sounds of the micro:bit, such as
+//! `Sound.GIGGLE` in MicroPython, are not provided. A [`Synth`] renders the samples, which can be
+//! played by a [`PcmSpeaker`](super::PcmSpeaker) with [`play_sound`](super::PcmSpeaker::play_sound).
+//! Speakers only playing notes, such as the [`AudioPlayer`](super::AudioPlayer), follow the
+//! frequencies with the tones of [`SoundExpression::notes`] instead.
+//!
+//! # Examples
+//!
+//! ```
+//! use microbit_bsp::speaker::{Curve, SoundEffect, SoundExpression, Synth, Waveform};
+//!
+//! const CHIRP: SoundExpression = SoundExpression::new(&[SoundEffect {
+//!     waveform: Waveform::Sine,
+//!     freq_start: 400,
+//!     freq_end: 1200,
+//!     duration_ms: 100,
+//!     shape: Curve::Linear,
+//!     ..SoundEffect::DEFAULT
+//! }]);
+//!
+//! let samples = Synth::new(&CHIRP, 8_000);
+//! assert_eq!(samples.count(), 800);
+//! ```
+use super::{Note, PcmTiming, Pitch};
+
+/// Sample rate used when playing sound expressions
+pub const SYNTH_RATE: u32 = 16_000;
+
+/// Length in milliseconds of the tones following the frequency sweeps of [`SoundExpression::notes`]
+pub const SWEEP_STEP_MS: u32 = 10;
+
+/// Shape of the sound wave
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Waveform {
+    /// Smooth and pure
+    Sine,
+    /// Bright and buzzy
+    Sawtooth,
+    /// Soft, between sine and square
+    Triangle,
+    /// Hollow, like classic video games
+    Square,
+    /// Random values, changing twice per period of the frequency
+    Noise,
+}
+
+/// How the frequency moves from the start to the end frequency
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Curve {
+    /// At a constant rate
+    Linear,
+    /// Slowly at first and at the end, and quickly in the middle
+    Curve,
+    /// Quickly at first, then slowing down
+    Logarithmic,
+}
+
+impl Curve {
+    /// Map the progress through the effect, from 0 to 65536, to the progress of the frequency
+    pub fn apply(&self, t: u32) -> u32 {
+        let t = u64::from(t.min(1 << 16));
+        let one = 1u64 << 16;
+        let shaped = match self {
+            Curve::Linear => t,
+            // Smoothstep, 3t² - 2t³
+            Curve::Curve => (t * t * (3 * one - 2 * t)) >> 32,
+            // Quadratic ease out, 2t - t²
+            Curve::Logarithmic => (t * (2 * one - t)) >> 16,
+        };
+        shaped as u32
+    }
+}
+
+/// A sweep of the frequency and the volume of a waveform
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SoundEffect {
+    /// Shape of the wave
+    pub waveform: Waveform,
+    /// Frequency at the start in Hz
+    pub freq_start: u32,
+    /// Frequency at the end in Hz
+    pub freq_end: u32,
+    /// Length of the effect
+    pub duration_ms: u32,
+    /// Volume at the start, from 0 to 255
+    pub vol_start: u8,
+    /// Volume at the end, from 0 to 255
+    pub vol_end: u8,
+    /// How the frequency moves between the start and the end
+    pub shape: Curve,
+}
+
+impl SoundEffect {
+    /// The default effect in MicroPython, a fading square wave sweeping up from 500 to 2500 Hz
+    pub const DEFAULT: Self = Self {
+        waveform: Waveform::Square,
+        freq_start: 500,
+        freq_end: 2500,
+        duration_ms: 500,
+        vol_start: 255,
+        vol_end: 0,
+        shape: Curve::Logarithmic,
+    };
+
+    /// Number of samples of the effect at the sample rate
+    pub fn samples(&self, rate: u32) -> u32 {
+        (u64::from(self.duration_ms) * u64::from(rate) / 1000) as u32
+    }
+
+    // Frequency and volume at the progress through the effect, from 0 to 65536
+    fn at(&self, t: u32) -> (u32, u32) {
+        let f = lerp(self.freq_start, self.freq_end, self.shape.apply(t));
+        let volume = lerp(self.vol_start.into(), self.vol_end.into(), t);
+        (f, volume)
+    }
+}
+
+impl Default for SoundEffect {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A series of sound effects played one after the other
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SoundExpression<'a> {
+    effects: &'a [SoundEffect],
+}
+
+impl<'a> SoundExpression<'a> {
+    /// Create an expression playing the effects in order
+    pub const fn new(effects: &'a [SoundEffect]) -> Self {
+        Self { effects }
+    }
+
+    /// The effects
+    pub fn effects(&self) -> &'a [SoundEffect] {
+        self.effects
+    }
+
+    /// Playing time in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        self.effects.iter().map(|e| e.duration_ms).sum()
+    }
+
+    /// Iterate over tones of [`SWEEP_STEP_MS`] following the frequencies of the effects, for
+    /// speakers that can only play notes. The waveforms are not reproduced, and the volume only
+    /// turns the tones into rests when it is 0.
+    pub fn notes(&self) -> SweepNotes<'a> {
+        SweepNotes {
+            effects: self.effects,
+            elapsed: 0,
+        }
+    }
+}
+
+/// Iterator over the tones approximating a [`SoundExpression`], see [`SoundExpression::notes`]
+pub struct SweepNotes<'a> {
+    effects: &'a [SoundEffect],
+    // Milliseconds of the current effect already played
+    elapsed: u32,
+}
+
+impl Iterator for SweepNotes<'_> {
+    type Item = Note;
+
+    fn next(&mut self) -> Option<Note> {
+        loop {
+            let effect = self.effects.first()?;
+            if self.elapsed >= effect.duration_ms {
+                self.effects = &self.effects[1..];
+                self.elapsed = 0;
+                continue;
+            }
+
+            // Tone at the middle of the step
+            let ms = SWEEP_STEP_MS.min(effect.duration_ms - self.elapsed);
+            let t = ((u64::from(self.elapsed) * 2 + u64::from(ms)) << 15) / u64::from(effect.duration_ms);
+            let (f, volume) = effect.at(t as u32);
+            self.elapsed += ms;
+            let pitch = if f == 0 || volume == 0 {
+                Pitch::Silent
+            } else {
+                Pitch::Frequency(f)
+            };
+            return Some(Note(pitch, ms));
+        }
+    }
+}
+
+/// Renders the samples of a [`SoundExpression`] at a sample rate
+pub struct Synth<'a> {
+    effects: &'a [SoundEffect],
+    rate: u32,
+    // Sample within the current effect, and the number of samples in the effect
+    sample: u32,
+    samples: u32,
+    phase: u32,
+    noise: u16,
+    noise_level: i16,
+}
+
+impl<'a> Synth<'a> {
+    /// Create a synth rendering the sound at the sample rate in Hz
+    pub fn new(sound: &SoundExpression<'a>, rate: u32) -> Self {
+        let mut synth = Self {
+            effects: sound.effects,
+            rate: rate.max(1),
+            sample: 0,
+            samples: 0,
+            phase: 0,
+            noise: 0xace1,
+            noise_level: 0,
+        };
+        synth.start_effect();
+        synth
+    }
+
+    /// Number of samples left to render
+    pub fn remaining(&self) -> usize {
+        let rest: u32 = self.effects.iter().skip(1).map(|e| e.samples(self.rate)).sum();
+        (rest + self.samples - self.sample) as usize
+    }
+
+    // Skip to the first effect with samples
+    fn start_effect(&mut self) {
+        self.sample = 0;
+        self.samples = 0;
+        while let Some(effect) = self.effects.first() {
+            self.samples = effect.samples(self.rate);
+            if self.samples > 0 {
+                return;
+            }
+            self.effects = &self.effects[1..];
+        }
+    }
+
+    /// Convert the next samples to PWM duty cycles, filling as much of the buffer as possible.
+    /// Returns the number of samples converted.
+    pub fn render(&mut self, timing: &PcmTiming, duty: &mut [u16]) -> usize {
+        let mut count = 0;
+        for (d, sample) in duty.iter_mut().zip(self.by_ref()) {
+            *d = timing.duty((sample as u16) ^ 0x8000);
+            count += 1;
+        }
+        count
+    }
+
+    // Pseudo random level for noise, from a 16 bit Galois LFSR
+    fn next_noise(&mut self) -> i16 {
+        let lsb = self.noise & 1;
+        self.noise >>= 1;
+        if lsb != 0 {
+            self.noise ^= 0xb400;
+        }
+        self.noise as i16
+    }
+}
+
+impl Iterator for Synth<'_> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let effect = *self.effects.first()?;
+
+        // Progress through the effect, from 0 to 65536
+        let t = (u64::from(self.sample) << 16) / u64::from(self.samples);
+        let (f, volume) = effect.at(t as u32);
+        let amplitude = (volume * 128) as i32;
+
+        let level = match effect.waveform {
+            Waveform::Sine => sine(self.phase),
+            Waveform::Sawtooth => ((self.phase >> 16) as u16 ^ 0x8000) as i16,
+            Waveform::Triangle => {
+                let p = (self.phase >> 15) as i32;
+                (if p < 0x10000 { p - 0x8000 } else { 0x17fff - p }) as i16
+            }
+            Waveform::Square => {
+                if self.phase < 1 << 31 {
+                    i16::MAX
+                } else {
+                    -i16::MAX
+                }
+            }
+            Waveform::Noise => self.noise_level,
+        };
+
+        let step = ((u64::from(f) << 32) / u64::from(self.rate)) as u32;
+        let next = self.phase.wrapping_add(step);
+        if (next ^ self.phase) & (1 << 31) != 0 {
+            self.noise_level = self.next_noise();
+        }
+        self.phase = next;
+
+        self.sample += 1;
+        if self.sample == self.samples {
+            self.effects = &self.effects[1..];
+            self.start_effect();
+        }
+        Some((i32::from(level) * amplitude / 0x8000) as i16)
+    }
+}
+
+// Interpolate between the values with the progress from 0 to 65536
+fn lerp(start: u32, end: u32, t: u32) -> u32 {
+    let (start, end) = (i64::from(start), i64::from(end));
+    (start + (((end - start) * i64::from(t)) >> 16)) as u32
+}
+
+// Sine of the phase, with a full turn of 2^32, using Bhaskara's approximation
+fn sine(phase: u32) -> i16 {
+    // Position in the half turn from 0 to 65536
+    let u = u64::from((phase >> 15) & 0xffff);
+    let y = (u * (0x10000 - u)) >> 16;
+    let level = (16 * y * 0x7fff / (5 * 0x10000 - 4 * y)) as i16;
+    if phase < 1 << 31 {
+        level
+    } else {
+        -level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Shorthand for the effects of the tests
+    const fn fx(waveform: Waveform, freq: (u32, u32), duration_ms: u32, vol: (u8, u8), shape: Curve) -> SoundEffect {
+        SoundEffect {
+            waveform,
+            freq_start: freq.0,
+            freq_end: freq.1,
+            duration_ms,
+            vol_start: vol.0,
+            vol_end: vol.1,
+            shape,
+        }
+    }
+
+    fn effect(waveform: Waveform, freq: u32, duration_ms: u32) -> SoundEffect {
+        fx(waveform, (freq, freq), duration_ms, (255, 255), Curve::Linear)
+    }
+
+    #[test]
+    fn test_curves() {
+        for curve in [Curve::Linear, Curve::Curve, Curve::Logarithmic] {
+            assert_eq!(curve.apply(0), 0);
+            assert_eq!(curve.apply(1 << 16), 1 << 16);
+            let mut last = 0;
+            for t in (0..=1 << 16).step_by(1024) {
+                assert!(curve.apply(t) >= last);
+                last = curve.apply(t);
+            }
+        }
+        assert_eq!(Curve::Linear.apply(0x4000), 0x4000);
+        assert_eq!(Curve::Curve.apply(0x8000), 0x8000);
+        assert!(Curve::Curve.apply(0x4000) < 0x4000);
+        assert!(Curve::Logarithmic.apply(0x4000) > 0x4000);
+    }
+
+    #[test]
+    fn test_length() {
+        let effects = [
+            effect(Waveform::Sine, 440, 100),
+            effect(Waveform::Square, 440, 0),
+            effect(Waveform::Noise, 440, 50),
+        ];
+        let sound = SoundExpression::new(&effects);
+        assert_eq!(sound.duration_ms(), 150);
+        let mut synth = Synth::new(&sound, 8_000);
+        assert_eq!(synth.remaining(), 1200);
+        synth.nth(99);
+        assert_eq!(synth.remaining(), 1100);
+        assert_eq!(synth.count(), 1100);
+        assert_eq!(Synth::new(&SoundExpression::new(&[]), 8_000).next(), None);
+    }
+
+    #[test]
+    fn test_waveforms() {
+        // 1 kHz at 8 kHz has 8 samples per period
+        let mut square = [0; 16];
+        let effects = [effect(Waveform::Square, 1000, 2)];
+        let sound = SoundExpression::new(&effects);
+        square
+            .iter_mut()
+            .zip(Synth::new(&sound, 8_000))
+            .for_each(|(s, v)| *s = v);
+        let high = 32639;
+        assert_eq!(square[..8], [high, high, high, high, -high, -high, -high, -high]);
+        assert_eq!(square[..8], square[8..]);
+
+        let effects = [effect(Waveform::Sine, 1000, 1)];
+        let sine: heapless::Vec<i16, 8> = Synth::new(&SoundExpression::new(&effects), 8_000).collect();
+        assert_eq!(sine[0], 0);
+        assert_eq!(sine[4], 0);
+        assert!(sine[2] > 32500 && sine[6] < -32500);
+        assert_eq!(sine[1], -sine[5]);
+        assert!((sine[1] - 23080).abs() < 100);
+
+        let effects = [effect(Waveform::Triangle, 1000, 1)];
+        let triangle: heapless::Vec<i16, 8> = Synth::new(&SoundExpression::new(&effects), 8_000).collect();
+        assert_eq!(triangle[0], -32640);
+        assert_eq!(triangle[2], 0);
+        assert_eq!(triangle[4], 32639);
+        assert_eq!(triangle[6], 0);
+
+        let effects = [effect(Waveform::Sawtooth, 1000, 1)];
+        let saw: heapless::Vec<i16, 8> = Synth::new(&SoundExpression::new(&effects), 8_000).collect();
+        assert_eq!(saw[0], -32640);
+        assert!(saw.windows(2).all(|w| w[0] < w[1]));
+
+        // Noise changes twice per period, with random levels
+        let effects = [effect(Waveform::Noise, 1000, 8)];
+        let noise: heapless::Vec<i16, 64> = Synth::new(&SoundExpression::new(&effects), 8_000).collect();
+        assert!(noise.chunks(4).skip(1).all(|c| c.iter().all(|&s| s == c[0])));
+        assert!(noise.chunks(4).skip(1).any(|c| c[0] > 0));
+        assert!(noise.chunks(4).skip(1).any(|c| c[0] < 0));
+    }
+
+    #[test]
+    fn test_sweep() {
+        // The volume fades linearly, and the frequency slides up
+        let effects = [fx(Waveform::Square, (100, 1000), 100, (255, 0), Curve::Linear)];
+        let sound = SoundExpression::new(&effects);
+        let samples: heapless::Vec<i16, 800> = Synth::new(&sound, 8_000).collect();
+        assert_eq!(samples[0], 32639);
+        assert!(samples[400].abs() > 16000 && samples[400].abs() < 16400);
+        assert!(samples[799].abs() < 100);
+
+        let crossings = |s: &[i16]| s.windows(2).filter(|w| (w[0] > 0) != (w[1] > 0)).count();
+        assert!(crossings(&samples[..200]) < crossings(&samples[600..]));
+    }
+
+    #[test]
+    fn test_render() {
+        let timing = PcmTiming::new(SYNTH_RATE).unwrap();
+        let effects = [
+            fx(Waveform::Sine, (700, 900), 70, (180, 140), Curve::Curve),
+            fx(Waveform::Triangle, (900, 600), 60, (140, 0), Curve::Logarithmic),
+        ];
+        let sound = SoundExpression::new(&effects);
+        let mut synth = Synth::new(&sound, SYNTH_RATE);
+        let mut duty = [0; 256];
+        let mut total = 0;
+        loop {
+            let count = synth.render(&timing, &mut duty);
+            assert!(duty[..count].iter().all(|&d| d <= timing.counter_top()));
+            total += count;
+            if count < duty.len() {
+                break;
+            }
+        }
+        assert_eq!(total as u32, sound.duration_ms() * SYNTH_RATE / 1000);
+    }
+
+    #[test]
+    fn test_notes() {
+        let effects = [
+            fx(Waveform::Sine, (1000, 2000), 40, (255, 255), Curve::Linear),
+            effect(Waveform::Square, 440, 0),
+            fx(Waveform::Noise, (300, 300), 15, (0, 0), Curve::Linear),
+        ];
+        let sound = SoundExpression::new(&effects);
+        let mut notes = sound.notes();
+
+        // Tones at the middle of each step, and rests where the volume is 0
+        for hz in [1125, 1375, 1625, 1875] {
+            assert_eq!(notes.next(), Some(Note(Pitch::Frequency(hz), 10)));
+        }
+        assert_eq!(notes.next(), Some(Note(Pitch::Silent, 10)));
+        assert_eq!(notes.next(), Some(Note(Pitch::Silent, 5)));
+        assert_eq!(notes.next(), None);
+        assert_eq!(sound.notes().map(|n| n.1).sum::<u32>(), sound.duration_ms());
+    }
+}
@@ -0,0 +1,365 @@
+//! Tone sequences rendered for playback by the PWM peripheral
+//!
+//! A [`ToneSequence`] is a buffer of PWM steps, each playing a number of periods of a square wave
+//! with its own frequency. The PWM loads the steps with DMA, changing the period at every step,
+//! so the notes are played with exact timing and without any CPU involvement. See
+//! [`SequenceSpeaker`](super::SequenceSpeaker) for playing them.
+//!
+//! # Examples
+//!
+//! ```
+//! use microbit_bsp::speaker::{Melody, NamedPitch, ToneSequence, WORDS_PER_STEP};
+//!
+//! let mut words = [0; 512 * WORDS_PER_STEP];
+//! let mut tones = ToneSequence::new(&mut words, 3);
+//! tones.melody(&Melody::parse("c4:1 e g").unwrap()).unwrap();
+//! tones.glissando(NamedPitch::C5.into(), NamedPitch::C6.into(), 200).unwrap();
+//! assert!(tones.steps() > 0);
+//! ```
+use core::fmt;
+
+use super::{Melody, Note, Pitch};
+
+/// Clock of the PWM counter, using the 16 MHz clock divided by 8
+pub const TONE_CLOCK_HZ: u32 = 2_000_000;
+
+/// Words per step of the sequence: the duty cycle of the speaker channel, two unused channels and
+/// the counter top setting the period
+pub const WORDS_PER_STEP: usize = 4;
+
+/// Lowest frequency that can be played, limited by the 15 bit counter
+pub const MIN_TONE_HZ: u32 = TONE_CLOCK_HZ / MAX_TOP as u32 + 1;
+
+/// Highest frequency that can be played
+pub const MAX_TONE_HZ: u32 = 20_000;
+
+// Largest counter top supported by the PWM
+const MAX_TOP: u16 = 0x7fff;
+
+// Period of the steps during rests, 1 ms
+const REST_TOP: u16 = (TONE_CLOCK_HZ / 1000) as u16;
+
+// Maximum number of words in a sequence supported by the PWM
+const MAX_WORDS: usize = 0x7fff;
+
+/// Errors produced when rendering tone sequences
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ToneError {
+    /// The buffer can not hold more steps
+    Full,
+    /// The frequency is outside [`MIN_TONE_HZ`] to [`MAX_TONE_HZ`]
+    Frequency(u32),
+}
+
+impl fmt::Display for ToneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToneError::Full => write!(f, "sequence full"),
+            ToneError::Frequency(hz) => write!(f, "unsupported frequency {} Hz", hz),
+        }
+    }
+}
+
+/// A sequence of tones rendered into a buffer of PWM steps.
+///
+/// Each step is repeated `refresh + 1` periods, so a larger refresh fits longer sequences in the
+/// buffer at the cost of a coarser timing. The sounds are rounded to whole steps, and the
+/// rounding is compensated by the following sounds.
+pub struct ToneSequence<'a> {
+    words: &'a mut [u16],
+    len: usize,
+    refresh: u32,
+    ticks: u64,
+    end: u64,
+}
+
+impl<'a> ToneSequence<'a> {
+    /// Create an empty sequence rendering into the buffer, repeating every step `refresh`
+    /// additional periods
+    pub fn new(words: &'a mut [u16], refresh: u32) -> Self {
+        let max = words.len().min(MAX_WORDS);
+        Self {
+            words: &mut words[..max - max % WORDS_PER_STEP],
+            len: 0,
+            refresh,
+            ticks: 0,
+            end: 0,
+        }
+    }
+
+    /// Remove all steps
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.ticks = 0;
+        self.end = 0;
+    }
+
+    /// The rendered steps, to be loaded by the PWM
+    pub fn words(&self) -> &[u16] {
+        &self.words[..self.len]
+    }
+
+    /// Number of rendered steps
+    pub fn steps(&self) -> usize {
+        self.len / WORDS_PER_STEP
+    }
+
+    /// Number of additional periods every step is repeated
+    pub fn refresh(&self) -> u32 {
+        self.refresh
+    }
+
+    /// Check if no steps have been rendered
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Playing time of the sequence in microseconds
+    pub fn duration_us(&self) -> u64 {
+        self.ticks * 1_000_000 / u64::from(TONE_CLOCK_HZ)
+    }
+
+    /// Playing time of the sequence in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        (self.ticks * 1000 / u64::from(TONE_CLOCK_HZ)) as u32
+    }
+
+    // Ticks of the PWM clock played by a step with the counter top
+    fn step_ticks(&self, top: u16) -> u64 {
+        u64::from(top) * (u64::from(self.refresh) + 1)
+    }
+
+    fn push(&mut self, top: u16, duty: u16) -> Result<(), ToneError> {
+        let step = self
+            .words
+            .get_mut(self.len..self.len + WORDS_PER_STEP)
+            .ok_or(ToneError::Full)?;
+        step.copy_from_slice(&[duty, 0, 0, top]);
+        self.len += WORDS_PER_STEP;
+        self.ticks += self.step_ticks(top);
+        Ok(())
+    }
+
+    // Add steps with the counter top until the sequence is as close as possible to the end tick
+    fn fill(&mut self, top: u16, duty: u16, end: u64) -> Result<(), ToneError> {
+        let step = self.step_ticks(top);
+        while self.ticks + step / 2 < end {
+            self.push(top, duty)?;
+        }
+        Ok(())
+    }
+
+    // Tick at which a sound of the length added now ends. Counted from the nominal end of the
+    // previous sound, so the rounding to whole steps does not accumulate.
+    fn end(&mut self, ms: u32) -> u64 {
+        self.end += u64::from(ms) * u64::from(TONE_CLOCK_HZ) / 1000;
+        self.end
+    }
+
+    /// Add a tone with the pitch lasting the length in milliseconds. If the buffer gets full,
+    /// the steps that fit are kept.
+    pub fn tone(&mut self, pitch: Pitch, ms: u32) -> Result<&mut Self, ToneError> {
+        let (top, duty) = square(pitch)?;
+        let end = self.end(ms);
+        self.fill(top, duty, end)?;
+        Ok(self)
+    }
+
+    /// Add a note
+    pub fn note(&mut self, note: &Note) -> Result<&mut Self, ToneError> {
+        self.tone(note.0, note.1)
+    }
+
+    /// Add all notes of the melody
+    pub fn melody(&mut self, melody: &Melody<'_>) -> Result<&mut Self, ToneError> {
+        for note in melody.notes() {
+            self.note(&note)?;
+        }
+        Ok(self)
+    }
+
+    /// Add a glissando sliding the frequency linearly between the pitches during the length in
+    /// milliseconds, changing the frequency at every step
+    pub fn glissando(&mut self, from: Pitch, to: Pitch, ms: u32) -> Result<&mut Self, ToneError> {
+        let (Some(from), Some(to)) = (frequency(from), frequency(to)) else {
+            return self.tone(Pitch::Silent, ms);
+        };
+        top(from)?;
+        top(to)?;
+
+        let start = self.ticks;
+        let end = self.end(ms);
+        let length = end.saturating_sub(start).max(1) as i64;
+        loop {
+            let elapsed = (self.ticks - start) as i64;
+            let hz = i64::from(from) + (i64::from(to) - i64::from(from)) * elapsed / length;
+            let top = top(hz as u32)?;
+            if self.ticks + self.step_ticks(top) / 2 >= end {
+                return Ok(self);
+            }
+            self.push(top, top / 2)?;
+        }
+    }
+
+    /// Add an arpeggio cycling through the pitches, playing each for the step length in
+    /// milliseconds, until the total length in milliseconds
+    pub fn arpeggio(&mut self, pitches: &[Pitch], step_ms: u32, ms: u32) -> Result<&mut Self, ToneError> {
+        for pitch in pitches {
+            square(*pitch)?;
+        }
+
+        let start = self.end;
+        let end = self.end(ms);
+        let step_ticks = u64::from(step_ms.max(1)) * u64::from(TONE_CLOCK_HZ) / 1000;
+        for (i, pitch) in (1..).zip(pitches.iter().cycle()) {
+            let step_end = (start + i * step_ticks).min(end);
+            let (top, duty) = square(*pitch)?;
+            self.fill(top, duty, step_end)?;
+            if step_end == end {
+                break;
+            }
+        }
+        Ok(self)
+    }
+}
+
+// Counter top and duty cycle of a square wave with the pitch
+fn square(pitch: Pitch) -> Result<(u16, u16), ToneError> {
+    match frequency(pitch) {
+        Some(hz) => top(hz).map(|top| (top, top / 2)),
+        None => Ok((REST_TOP, 0)),
+    }
+}
+
+// Frequency of the pitch, or None for rests
+fn frequency(pitch: Pitch) -> Option<u32> {
+    match pitch {
+        Pitch::Silent => None,
+        Pitch::Named(n) => Some(n.into_frequency()),
+        Pitch::Frequency(hz) => Some(hz),
+    }
+}
+
+// Counter top for the frequency
+fn top(hz: u32) -> Result<u16, ToneError> {
+    if !(MIN_TONE_HZ..=MAX_TONE_HZ).contains(&hz) {
+        return Err(ToneError::Frequency(hz));
+    }
+    Ok(((TONE_CLOCK_HZ + hz / 2) / hz) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::speaker::NamedPitch;
+
+    // Frequency of each step
+    fn frequencies<'a>(tones: &'a ToneSequence<'_>) -> impl Iterator<Item = u32> + 'a {
+        tones.words().chunks(WORDS_PER_STEP).map(|step| match step {
+            [0, _, _, REST_TOP] => 0,
+            [duty, 0, 0, top] => {
+                assert_eq!(*duty, top / 2);
+                TONE_CLOCK_HZ / u32::from(*top)
+            }
+            _ => panic!("invalid step {:?}", step),
+        })
+    }
+
+    #[test]
+    fn test_tone() {
+        let mut words = [0; 64 * WORDS_PER_STEP];
+        let mut tones = ToneSequence::new(&mut words, 99);
+        assert!(tones.is_empty());
+
+        // 100 periods of 1 kHz per step
+        tones.tone(Pitch::Frequency(1000), 300).unwrap();
+        assert_eq!(tones.steps(), 3);
+        assert_eq!(tones.words()[..WORDS_PER_STEP], [1000, 0, 0, 2000]);
+        assert_eq!(tones.duration_ms(), 300);
+
+        // Rests use 1 ms periods, and tones are rounded to whole steps
+        tones
+            .tone(Pitch::Silent, 200)
+            .unwrap()
+            .tone(NamedPitch::A4.into(), 500)
+            .unwrap();
+        assert!(frequencies(&tones).eq([1000, 1000, 1000, 0, 0, 440, 440].into_iter()));
+        assert_eq!(tones.duration_ms(), 954);
+
+        // Rounding of short steps does not accumulate
+        tones.clear();
+        for _ in 0..10 {
+            tones.tone(Pitch::Frequency(1000), 150).unwrap();
+        }
+        assert_eq!(tones.duration_ms(), 1500);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut words = [0; 2 * WORDS_PER_STEP + 3];
+        let mut tones = ToneSequence::new(&mut words, 0);
+        assert_eq!(
+            tones.tone(Pitch::Frequency(40), 10).err(),
+            Some(ToneError::Frequency(40))
+        );
+        assert_eq!(
+            tones.tone(Pitch::Frequency(30_000), 10).err(),
+            Some(ToneError::Frequency(30_000))
+        );
+        assert!(tones.is_empty());
+
+        assert_eq!(tones.tone(Pitch::Silent, 3).err(), Some(ToneError::Full));
+        assert_eq!(tones.steps(), 2);
+        assert_eq!(tones.duration_ms(), 2);
+    }
+
+    #[test]
+    fn test_melody() {
+        let mut words = [0; 256 * WORDS_PER_STEP];
+        let mut tones = ToneSequence::new(&mut words, 3);
+        let melody = Melody::parse("c4:1 r e").unwrap();
+        tones.melody(&melody).unwrap();
+        assert!(tones.duration_ms().abs_diff(melody.duration_ms()) < 8);
+        // Counter tops are rounded to the nearest frequency
+        let mut steps = frequencies(&tones);
+        assert!(steps.by_ref().take_while(|&hz| hz != 0).all(|hz| hz == 260));
+        assert!(steps.skip_while(|&hz| hz == 0).all(|hz| hz == 329));
+    }
+
+    #[test]
+    fn test_glissando() {
+        let mut words = [0; 256 * WORDS_PER_STEP];
+        let mut tones = ToneSequence::new(&mut words, 0);
+        tones
+            .glissando(Pitch::Frequency(1000), Pitch::Frequency(2000), 100)
+            .unwrap();
+        let steps: heapless::Vec<u32, 256> = frequencies(&tones).collect();
+        assert_eq!(steps.first(), Some(&1000));
+        assert!(*steps.last().unwrap() > 1950);
+        assert!(steps.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(tones.duration_ms(), 100);
+
+        // Downwards
+        tones.clear();
+        tones
+            .glissando(Pitch::Frequency(2000), Pitch::Frequency(1000), 50)
+            .unwrap();
+        let steps: heapless::Vec<u32, 256> = frequencies(&tones).collect();
+        assert!(steps.windows(2).all(|w| w[0] >= w[1]));
+        assert!(tones.duration_ms().abs_diff(50) <= 1);
+    }
+
+    #[test]
+    fn test_arpeggio() {
+        let mut words = [0; 256 * WORDS_PER_STEP];
+        let mut tones = ToneSequence::new(&mut words, 9);
+        let chord = [Pitch::Frequency(500), Pitch::Frequency(1000), Pitch::Frequency(2000)];
+        tones.arpeggio(&chord, 20, 100).unwrap();
+        assert_eq!(tones.duration_ms(), 100);
+
+        // Each pitch plays 20 ms, i.e. 1, 2 and 4 steps of 10 periods
+        let steps: heapless::Vec<u32, 256> = frequencies(&tones).collect();
+        assert_eq!(steps, [500, 1000, 1000, 2000, 2000, 2000, 2000, 500, 1000, 1000]);
+    }
+}